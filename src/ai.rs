@@ -0,0 +1,579 @@
+pub mod ai {
+    use std::collections::{HashMap, HashSet};
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::{Danger, Faction, Game, GameMap, House, Job, Person, Position, Skills, Terrain};
+    use crate::recipes::recipes::{Recipe, RecipeBook};
+    use crate::resources::resources::{GatherMethod, ResourceKind, ResourceRegistry};
+    use crate::stockpile::stockpile::{load_stockpiles, nearest, store_stockpiles};
+    use crate::wildlife::wildlife::Animal;
+    use crate::raiders::raiders::{Barracks, Raider};
+    use crate::research::research::{Bonus, Library, study};
+    use crate::earthworks::earthworks::{Earthwork, dig};
+    use crate::workshop::workshop::Workshop;
+    use rand::Rng;
+    use rand::rngs::StdRng;
+    use legion::{Entity, IntoQuery};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Activity {
+        Idle,
+        Eat,
+        Sleep,
+        Work,
+        Haul,
+        Socialise,
+        Flee,
+        Defend,
+    }
+
+    impl Activity {
+        pub const CANDIDATES: [Activity; 7] = [
+            Activity::Eat,
+            Activity::Sleep,
+            Activity::Work,
+            Activity::Haul,
+            Activity::Socialise,
+            Activity::Flee,
+            Activity::Defend,
+        ];
+
+        pub fn name(&self) -> &'static str {
+            return match self {
+                Activity::Idle => "Idle",
+                Activity::Eat => "Eat",
+                Activity::Sleep => "Sleep",
+                Activity::Work => "Work",
+                Activity::Haul => "Haul",
+                Activity::Socialise => "Socialise",
+                Activity::Flee => "Flee",
+                Activity::Defend => "Defend",
+            };
+        }
+    }
+
+    /// What a villager has decided to do, where they are heading, and the scores that led to
+    /// the decision so it can be shown in the inspector.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Intent {
+        pub activity: Activity,
+        pub target: Option<Position>,
+        pub scores: Vec<(Activity, i32)>,
+    }
+
+    impl Intent {
+        pub fn new() -> Intent {
+            return Intent { activity: Activity::Idle, target: None, scores: Vec::new() }
+        }
+    }
+
+    /// The parts of the world a villager considers when scoring activities.
+    struct Context<'a> {
+        gathering: Option<&'a ResourceKind>,
+        /// The villager's workshop, when it has work waiting for them or they are bringing
+        /// it inputs.
+        workshop: Option<Position>,
+        /// The stockpile a crafter fetches inputs from while their workshop is short of them.
+        supplier: Option<Position>,
+        /// The villager's library, while the colony has something to research.
+        library: Option<Position>,
+        /// The earthwork the villager is labouring on.
+        earthwork: Option<Position>,
+        /// The nearest animal a hunter could go after, with the tile they would catch it from.
+        prey: Option<(Entity, Position, Position)>,
+        home: Option<Position>,
+        food_store: Option<Position>,
+        free_store: Option<Position>,
+        night: bool,
+        season_percent: i32,
+        nearest_villager: Option<Position>,
+        nearest_danger: Option<Position>,
+        /// The nearest raider threatening a militia member's barracks.
+        nearest_raider: Option<Position>,
+    }
+
+    fn score(activity: Activity, person: &Person, context: &Context) -> i32 {
+        return match activity {
+            Activity::Idle => 0,
+            Activity::Eat => if context.food_store.is_some() { person.hunger * 3 / 2 } else { 0 },
+            Activity::Sleep => {
+                let tiredness = Person::MAX_NEED - person.rest;
+                if context.night { tiredness + 50 } else { tiredness }
+            }
+            Activity::Work => {
+                let gathers = context.gathering.is_some_and(|kind| match kind.method {
+                    GatherMethod::Farm | GatherMethod::Gather => true,
+                    GatherMethod::Hunt => context.prey.is_some(),
+                    GatherMethod::Craft => false,
+                });
+                let building = context.workshop.or(context.supplier).or(context.library).or(context.earthwork);
+                let has_work = gathers || building.is_some();
+                if !has_work || !person.is_adult() || person.is_exhausted() || person.carrying.total() >= CARRY_CAPACITY {
+                    0
+                } else if context.night {
+                    10
+                } else if building.is_some() {
+                    50
+                } else {
+                    50 * context.season_percent / 100
+                }
+            }
+            Activity::Haul => {
+                if context.free_store.is_some() { person.carrying.total() * 100 / CARRY_CAPACITY } else { 0 }
+            }
+            Activity::Socialise => {
+                if context.nearest_villager.is_some() { person.social * 2 / 3 } else { 0 }
+            }
+            Activity::Flee => if context.nearest_danger.is_some() { 200 } else { 0 },
+            // militia stand their ground rather than flee
+            Activity::Defend => if context.nearest_raider.is_some() { 250 } else { 0 },
+        };
+    }
+
+    /// Scores every candidate and picks the best. The current activity gets a bonus so
+    /// villagers see a task through instead of flip-flopping between close scores.
+    fn decide(person: &Person, intent: &Intent, context: &Context) -> (Activity, Vec<(Activity, i32)>) {
+        let scores: Vec<(Activity, i32)> = Activity::CANDIDATES.iter()
+            .map(|activity| (*activity, score(*activity, person, context)))
+            .collect();
+        let mut best = (Activity::Idle, 0);
+        for (activity, score) in scores.iter() {
+            let score = if *activity == intent.activity { score + COMMITMENT_BONUS } else { *score };
+            if score > best.1 {
+                best = (*activity, score);
+            }
+        }
+        return (best.0, scores);
+    }
+
+    fn next_to(map: &GameMap, position: Position, sources: &[Terrain]) -> bool {
+        return [(0, -1), (0, 1), (-1, 0), (1, 0)].iter()
+            .any(|(x_offset, y_offset)| sources.contains(&map.terrain(position.x + x_offset, position.y + y_offset)));
+    }
+
+    /// Whether a villager can do their job standing at the given position.
+    fn is_work_site(map: &GameMap, job: &ResourceKind, position: Position) -> bool {
+        if map.is_tile_blocked(position.x, position.y) {
+            return false;
+        }
+        return match job.method {
+            GatherMethod::Farm => map.get_tile(position.x as usize, position.y as usize).fertility > 0,
+            GatherMethod::Gather => next_to(map, position, &job.sources),
+            GatherMethod::Hunt | GatherMethod::Craft => false,
+        };
+    }
+
+    fn pick_work_site(map: &GameMap, rng: &mut StdRng, job: &ResourceKind, position: Position, home: Position) -> Position {
+        if position.distance(home) <= WORK_RADIUS && is_work_site(map, job, position) {
+            return position;
+        }
+        for _ in 0..20 {
+            let x = home.x + rng.gen_range(-WORK_RADIUS, WORK_RADIUS + 1);
+            let y = home.y + rng.gen_range(-WORK_RADIUS, WORK_RADIUS + 1);
+            if is_work_site(map, job, Position::new(x, y)) {
+                return Position::new(x, y);
+            }
+        }
+        return position;
+    }
+
+    /// The open tile a hunter would catch an animal from: the animal's own tile or one next
+    /// to it, whichever is closest. Fish can only be caught from the shore, so there is
+    /// nowhere to catch one out in open water from.
+    fn hunting_spot(map: &GameMap, from: Position, animal: Position) -> Option<Position> {
+        return (-1..=1)
+            .flat_map(|y_offset| (-1..=1).map(move |x_offset| Position::new(animal.x + x_offset, animal.y + y_offset)))
+            .filter(|spot| !map.is_tile_blocked(spot.x, spot.y))
+            .min_by_key(|spot| from.distance(*spot));
+    }
+
+    fn recipe_of<'a>(recipes: &'a RecipeBook, workshop: &Workshop) -> &'a Recipe {
+        return &recipes.workshops[workshop.kind].recipes[workshop.recipe];
+    }
+
+    /// Moves one tile towards the target, sliding along an axis when the direct step is blocked.
+    pub(crate) fn step_towards(map: &GameMap, position: &mut Position, target: Position) {
+        let x_delta = (target.x - position.x).signum();
+        let y_delta = (target.y - position.y).signum();
+        for (x_delta, y_delta) in &[(x_delta, y_delta), (x_delta, 0), (0, y_delta)] {
+            if *x_delta == 0 && *y_delta == 0 {
+                continue;
+            }
+            let (x, y) = (position.x + x_delta, position.y + y_delta);
+            if !map.is_tile_blocked(x, y) {
+                position.x = x;
+                position.y = y;
+                return;
+            }
+        }
+    }
+
+    pub(crate) fn ai_system(game: &mut Game, rng: &mut StdRng, time_delta: u128) {
+        let mut house_query = <(Entity, &House, &Position)>::query();
+        let homes: HashMap<Entity, Position> = house_query.iter(&game.world)
+            .map(|(entity, _, position)| (*entity, *position))
+            .collect();
+        let mut villager_query = <(Entity, &Person, &Position, &Faction)>::query();
+        let villagers: Vec<(Entity, Position, Faction)> = villager_query.iter(&game.world)
+            .map(|(entity, _, position, faction)| (*entity, *position, *faction))
+            .collect();
+        let mut danger_query = <(&Danger, &Position)>::query();
+        let dangers: Vec<Position> = danger_query.iter(&game.world)
+            .map(|(_, position)| *position)
+            .collect();
+        let mut raider_query = <(&Raider, &Position)>::query();
+        let raiders: Vec<Position> = raider_query.iter(&game.world)
+            .map(|(_, position)| *position)
+            .collect();
+        let mut barracks_query = <(Entity, &Barracks, &Position)>::query();
+        let barracks: HashMap<Entity, Position> = barracks_query.iter(&game.world)
+            .map(|(entity, _, position)| (*entity, *position))
+            .collect();
+        let settlements = &game.settlements;
+        let mut animal_query = <(Entity, &Animal, &Position)>::query();
+        let animals: Vec<(Entity, Animal, Position)> = animal_query.iter(&game.world)
+            .map(|(entity, animal, position)| (*entity, *animal, *position))
+            .collect();
+        let mut killed = HashSet::new();
+
+        let mut library_query = <(Entity, &Library, &Position)>::query();
+        let libraries: HashMap<Entity, Position> = library_query.iter(&game.world)
+            .map(|(entity, _, position)| (*entity, *position))
+            .collect();
+        let mut earthwork_query = <(Entity, &Earthwork, &Position)>::query();
+        let earthworks: HashMap<Entity, Position> = earthwork_query.iter(&game.world)
+            .map(|(entity, _, position)| (*entity, *position))
+            .collect();
+        let mut labour: HashMap<Entity, u128> = HashMap::new();
+        let researching = game.research.current.is_some();
+        let mut studied = 0;
+        let player_farming_percent = 100 + game.research.bonus(Bonus::Farming);
+        let player_gathering_percent = 100 + game.research.bonus(Bonus::Gathering);
+        let player_crafting_percent = 100 + game.research.bonus(Bonus::Crafting);
+        let player_study_percent = 100 + game.research.bonus(Bonus::Study);
+
+        let mut workshop_query = <(Entity, &Workshop, &Position)>::query();
+        let mut workshops: HashMap<Entity, (Workshop, Position)> = workshop_query.iter(&game.world)
+            .map(|(entity, workshop, position)| (*entity, (workshop.clone(), *position)))
+            .collect();
+        let mut stockpiles = load_stockpiles(&game.world);
+        let edible = game.resources.edible();
+        let registry: &ResourceRegistry = &game.resources;
+        let recipes: &RecipeBook = &game.recipes;
+        let night = game.calendar.is_night();
+        let season_percent = game.calendar.season.fertility_percent();
+
+        let mut query = <(Entity, &mut Person, &mut Position, &mut Intent, &Faction)>::query();
+        for (entity, person, position, intent, faction) in query.iter_mut(&mut game.world) {
+            person.time_since_last_action += time_delta;
+            // stepping off a road takes less time than anything else a villager does
+            if person.time_since_last_action < game.map.move_time(position.x, position.y, Person::TIME_BETWEEN_ACTIONS) {
+                continue;
+            }
+            // the research is the player's own, so other settlements work at the usual rate
+            let (farming_percent, gathering_percent, crafting_percent, study_percent) = if *faction == Faction::PLAYER {
+                (player_farming_percent, player_gathering_percent, player_crafting_percent, player_study_percent)
+            } else {
+                (100, 100, 100, 100)
+            };
+
+            let here = *position;
+            let food_store = nearest(&stockpiles, *faction, here, |stockpile| stockpile.first_of(&edible, MEAL_SIZE).is_some());
+            let free_store = nearest(&stockpiles, *faction, here, |stockpile| stockpile.space() > 0);
+            // villagers of a hostile settlement are as much a danger as anything else
+            let enemies = villagers.iter()
+                .filter(|(_, _, other)| settlements.is_hostile(*faction, *other))
+                .map(|(_, other_position, _)| other_position);
+            let gathering = match person.job {
+                Some(Job::Gatherer(id)) => Some(registry.get(id)),
+                _ => None,
+            };
+            // a crafter whose workshop is short of inputs fetches a run's worth from a stockpile
+            let crafting = match person.job {
+                Some(Job::Crafter(building)) => workshops.get(&building),
+                _ => None,
+            };
+            let bringing_inputs = crafting.is_some_and(|(workshop, _)| {
+                let inputs = &recipe_of(recipes, workshop).inputs;
+                !workshop.input.has_all(inputs) && person.carrying.has_all(inputs)
+            });
+            let supplier = match crafting {
+                Some((workshop, _)) if !bringing_inputs && !workshop.has_work(recipe_of(recipes, workshop)) => {
+                    let inputs = &recipe_of(recipes, workshop).inputs;
+                    nearest(&stockpiles, *faction, here, |stockpile| stockpile.inventory.has_all(inputs))
+                }
+                _ => None,
+            };
+            let map = &game.map;
+            let hunting_ground = person.home.and_then(|home| homes.get(&home)).copied().unwrap_or(here);
+            let context = Context {
+                gathering,
+                prey: gathering.and_then(|kind| {
+                    animals.iter()
+                        .filter(|(animal, details, position)| {
+                            kind.prey.contains(&details.species)
+                                && !killed.contains(animal)
+                                && hunting_ground.distance(*position) <= HUNT_RADIUS
+                        })
+                        .filter_map(|(animal, _, position)| {
+                            hunting_spot(map, here, *position).map(|spot| (*animal, *position, spot))
+                        })
+                        .min_by_key(|(_, _, spot)| here.distance(*spot))
+                }),
+                workshop: crafting
+                    .filter(|(workshop, _)| bringing_inputs || workshop.has_work(recipe_of(recipes, workshop)))
+                    .map(|(_, position)| *position),
+                supplier: supplier.map(|index| stockpiles[index].position),
+                library: match person.job {
+                    Some(Job::Scholar(building)) if researching => libraries.get(&building).copied(),
+                    _ => None,
+                },
+                earthwork: match person.job {
+                    Some(Job::Labourer(site)) => earthworks.get(&site).copied(),
+                    _ => None,
+                },
+                home: person.home.and_then(|home| homes.get(&home)).copied(),
+                food_store: food_store.map(|index| stockpiles[index].position),
+                free_store: free_store.map(|index| stockpiles[index].position),
+                night,
+                season_percent,
+                nearest_villager: villagers.iter()
+                    .filter(|(other, _, other_faction)| other != entity && other_faction == faction)
+                    .filter(|(_, other_position, _)| here.distance(*other_position) <= SOCIAL_RADIUS)
+                    .min_by_key(|(_, other_position, _)| here.distance(*other_position))
+                    .map(|(_, other_position, _)| *other_position),
+                nearest_danger: dangers.iter()
+                    .chain(enemies)
+                    .filter(|danger| here.distance(**danger) <= FLEE_RADIUS)
+                    .min_by_key(|danger| here.distance(**danger))
+                    .copied(),
+                nearest_raider: match person.job {
+                    Some(Job::Militia(post)) => barracks.get(&post).and_then(|post| {
+                        raiders.iter()
+                            .filter(|raider| post.distance(**raider) <= DEFEND_RADIUS)
+                            .min_by_key(|raider| here.distance(**raider))
+                            .copied()
+                    }),
+                    _ => None,
+                },
+            };
+
+            let (activity, scores) = decide(person, intent, &context);
+            intent.scores = scores;
+            if activity != intent.activity {
+                intent.activity = activity;
+                intent.target = None;
+            }
+
+            let home = context.home.unwrap_or(here);
+            let target = match activity {
+                Activity::Idle => here,
+                Activity::Sleep => home,
+                Activity::Eat => context.food_store.unwrap_or(here),
+                Activity::Haul => context.free_store.unwrap_or(here),
+                Activity::Work => match (intent.target, context.workshop.or(context.supplier).or(context.library).or(context.earthwork), context.gathering) {
+                    (_, Some(workshop), _) => workshop,
+                    (_, None, Some(kind)) if kind.method == GatherMethod::Hunt => {
+                        context.prey.map_or(here, |(_, _, spot)| spot)
+                    }
+                    (Some(site), None, _) => site,
+                    (None, None, Some(kind)) => pick_work_site(&game.map, rng, kind, here, home),
+                    (None, None, None) => here,
+                },
+                Activity::Socialise => context.nearest_villager.unwrap_or(here),
+                Activity::Flee => {
+                    let danger = context.nearest_danger.unwrap_or(here);
+                    Position::new(here.x + (here.x - danger.x).signum() * 2, here.y + (here.y - danger.y).signum() * 2)
+                }
+                Activity::Defend => context.nearest_raider.unwrap_or(here),
+            };
+            intent.target = Some(target);
+
+            // buildings block their tile, so standing next to one is close enough
+            let arrived = match activity {
+                Activity::Eat | Activity::Sleep | Activity::Haul | Activity::Socialise | Activity::Defend => here.distance(target) <= 1,
+                Activity::Work if context.workshop.or(context.supplier).or(context.library).or(context.earthwork).is_some() => {
+                    here.distance(target) <= 1
+                }
+                Activity::Work if context.prey.is_some() => {
+                    context.prey.is_some_and(|(_, animal, _)| here.distance(animal) <= 1)
+                }
+                _ => here == target,
+            };
+            if !arrived {
+                person.time_since_last_action = 0;
+                step_towards(&game.map, position, target);
+                continue;
+            }
+            if person.time_since_last_action < Person::TIME_BETWEEN_ACTIONS {
+                continue;
+            }
+            person.time_since_last_action = 0;
+
+            match activity {
+                Activity::Eat => {
+                    let stockpile = food_store.map(|index| &mut stockpiles[index].stockpile);
+                    if let Some(stockpile) = stockpile {
+                        if let Some(meal) = stockpile.first_of(&edible, MEAL_SIZE) {
+                            stockpile.inventory.take(meal, MEAL_SIZE);
+                            person.hunger = (person.hunger - HUNGER_PER_DAY).max(0);
+                        }
+                    }
+                }
+                Activity::Sleep => {
+                    person.rest = (person.rest + REST_PER_SLEEP).min(Person::MAX_NEED);
+                }
+                Activity::Work => {
+                    let (id, job) = match person.job {
+                        Some(Job::Gatherer(id)) => (id, registry.get(id)),
+                        Some(Job::Crafter(building)) => {
+                            if context.workshop.is_none() {
+                                if let (Some(index), Some((workshop, _))) = (supplier, workshops.get(&building)) {
+                                    // another crafter may have emptied the stockpile earlier this tick
+                                    let inputs = &recipe_of(recipes, workshop).inputs;
+                                    let load: i32 = inputs.iter().map(|(_, amount)| amount).sum();
+                                    let inventory = &mut stockpiles[index].stockpile.inventory;
+                                    if inventory.has_all(inputs) && person.carrying.total() + load <= CARRY_CAPACITY {
+                                        for (id, amount) in inputs.iter() {
+                                            if inventory.take(*id, *amount) {
+                                                person.pick_up(*id, *amount);
+                                            }
+                                        }
+                                    }
+                                }
+                                continue;
+                            }
+                            if let Some((workshop, _)) = workshops.get_mut(&building) {
+                                let recipe = recipe_of(recipes, workshop);
+                                if bringing_inputs {
+                                    for (id, amount) in recipe.inputs.iter() {
+                                        if person.carrying.take(*id, *amount) {
+                                            workshop.input.add(*id, *amount);
+                                        }
+                                    }
+                                }
+                                workshop.work(recipe, Person::TIME_BETWEEN_ACTIONS * crafting_percent as u128 / 100);
+                                // finished goods go back with the worker on their next haul
+                                let finished: Vec<_> = workshop.output.iter().collect();
+                                for (id, amount) in finished {
+                                    let taken = person.pick_up(id, amount);
+                                    workshop.output.take(id, taken);
+                                }
+                                person.rest = (person.rest - REST_PER_HARVEST).max(0);
+                            }
+                            continue;
+                        }
+                        Some(Job::Scholar(_)) => {
+                            if context.library.is_some() {
+                                studied += RESEARCH_PER_STUDY * study_percent / 100;
+                                person.rest = (person.rest - REST_PER_HARVEST).max(0);
+                            }
+                            continue;
+                        }
+                        Some(Job::Labourer(site)) => {
+                            *labour.entry(site).or_insert(0) += Person::TIME_BETWEEN_ACTIONS;
+                            person.rest = (person.rest - REST_PER_HARVEST).max(0);
+                            continue;
+                        }
+                        Some(Job::Militia(_)) | None => continue,
+                    };
+                    if job.method == GatherMethod::Hunt {
+                        if let Some((animal, _, _)) = context.prey {
+                            killed.insert(animal);
+                            let catch = HUNT_YIELD * Skills::yield_percent(person.skills.gathering) / 100 * gathering_percent / 100;
+                            person.skills.gathering = (person.skills.gathering + 1).min(Skills::MAX_LEVEL);
+                            person.pick_up(id, catch);
+                            person.rest = (person.rest - REST_PER_HARVEST).max(0);
+                        }
+                        continue;
+                    }
+                    if !is_work_site(&game.map, job, here) {
+                        intent.target = None;
+                        continue;
+                    }
+                    let gathered = match job.method {
+                        GatherMethod::Farm => {
+                            let harvest = game.map.harvest(here.x, here.y, game.calendar.season);
+                            let harvest = harvest * Skills::yield_percent(person.skills.farming) / 100 * farming_percent / 100;
+                            person.skills.farming = (person.skills.farming + 1).min(Skills::MAX_LEVEL);
+                            harvest * game.weather.fertility_percent(here.x, here.y) / 100
+                        }
+                        GatherMethod::Gather => {
+                            let harvest = GATHER_YIELD * Skills::yield_percent(person.skills.gathering) / 100 * gathering_percent / 100;
+                            person.skills.gathering = (person.skills.gathering + 1).min(Skills::MAX_LEVEL);
+                            harvest
+                        }
+                        GatherMethod::Hunt | GatherMethod::Craft => 0,
+                    };
+                    person.pick_up(id, gathered);
+                    person.rest = (person.rest - REST_PER_HARVEST).max(0);
+                }
+                Activity::Haul => {
+                    if let Some(index) = free_store {
+                        let stockpile = &mut stockpiles[index].stockpile;
+                        let load: Vec<_> = person.carrying.iter().collect();
+                        for (id, amount) in load {
+                            let delivered = stockpile.store(id, amount);
+                            person.carrying.take(id, delivered);
+                        }
+                    }
+                }
+                Activity::Socialise => {
+                    person.social = (person.social - SOCIAL_PER_CHAT).max(0);
+                }
+                // the combat system does the fighting once a raider is in reach
+                Activity::Idle | Activity::Flee | Activity::Defend => {}
+            }
+        }
+        store_stockpiles(&mut game.world, &stockpiles);
+        study(game, studied);
+        dig(game, labour);
+        for animal in killed {
+            game.world.remove(animal);
+        }
+        let mut workshop_query = <(Entity, &mut Workshop)>::query();
+        for (entity, workshop) in workshop_query.iter_mut(&mut game.world) {
+            if let Some((snapshot, _)) = workshops.remove(entity) {
+                *workshop = snapshot;
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::test_maps::test_maps::map_from;
+
+        const LAKE: [&str; 5] = [
+            ".....",
+            ".~~~.",
+            ".~~~.",
+            ".~~~.",
+            ".....",
+        ];
+
+        #[test]
+        fn fish_are_caught_from_the_shore() {
+            let (map, _) = map_from(&LAKE);
+            let hunter = Position::new(5, 5);
+            let fish = Position::new(6, 6);
+            assert_eq!(hunting_spot(&map, hunter, fish), Some(hunter));
+            // of the shore tiles next to the fish, the hunter goes to the closest
+            let fish = Position::new(7, 6);
+            assert_eq!(hunting_spot(&map, Position::new(9, 5), fish), Some(Position::new(8, 5)));
+        }
+
+        #[test]
+        fn fish_out_in_open_water_cannot_be_caught() {
+            let (map, _) = map_from(&LAKE);
+            assert_eq!(hunting_spot(&map, Position::new(5, 5), Position::new(7, 7)), None);
+        }
+
+        #[test]
+        fn animals_on_land_are_caught_where_they_stand() {
+            let (map, _) = map_from(&LAKE);
+            let deer = Position::new(9, 7);
+            assert_eq!(hunting_spot(&map, Position::new(9, 9), deer), Some(Position::new(9, 8)));
+            assert_eq!(hunting_spot(&map, deer, deer), Some(deer));
+        }
+    }
+}
@@ -0,0 +1,112 @@
+pub mod calendar {
+    use crate::settings::settings::*;
+    use serde::Deserialize;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+    pub(crate) enum Season {
+        Spring,
+        Summer,
+        Autumn,
+        Winter,
+    }
+
+    impl Season {
+        pub fn next(&self) -> Season {
+            return match self {
+                Season::Spring => Season::Summer,
+                Season::Summer => Season::Autumn,
+                Season::Autumn => Season::Winter,
+                Season::Winter => Season::Spring,
+            };
+        }
+
+        pub fn name(&self) -> &'static str {
+            return match self {
+                Season::Spring => "Spring",
+                Season::Summer => "Summer",
+                Season::Autumn => "Autumn",
+                Season::Winter => "Winter",
+            };
+        }
+
+        /// Percentage of a tile's fertility that can be harvested this season.
+        pub fn fertility_percent(&self) -> i32 {
+            return match self {
+                Season::Spring => 75,
+                Season::Summer => 100,
+                Season::Autumn => 150,
+                Season::Winter => 0,
+            };
+        }
+    }
+
+    /// Game time measured in days, seasons and years. A day is `MILLIS_PER_DAY` of real time
+    /// and is split evenly into night, day and night again around noon.
+    pub struct Calendar {
+        time_of_day: u128,
+        pub days_elapsed: i32,
+        pub day: i32,
+        pub season: Season,
+        pub year: i32,
+    }
+
+    impl Calendar {
+        pub fn new() -> Calendar {
+            return Calendar {
+                time_of_day: MILLIS_PER_DAY / 4,
+                days_elapsed: 0,
+                day: 1,
+                season: Season::Spring,
+                year: 1,
+            }
+        }
+
+        /// Moves time forward, returning true when a new day begins.
+        pub fn advance(&mut self, time_delta: u128) -> bool {
+            self.time_of_day += time_delta;
+            if self.time_of_day < MILLIS_PER_DAY {
+                return false;
+            }
+            self.time_of_day -= MILLIS_PER_DAY;
+            self.days_elapsed += 1;
+            self.day += 1;
+            if self.day > DAYS_PER_SEASON {
+                self.day = 1;
+                if self.season == Season::Winter {
+                    self.year += 1;
+                }
+                self.season = self.season.next();
+            }
+            return true;
+        }
+
+        /// Fraction of the day that has passed, where 0.5 is noon.
+        pub fn time_of_day(&self) -> f32 {
+            return self.time_of_day as f32 / MILLIS_PER_DAY as f32;
+        }
+
+        pub fn is_night(&self) -> bool {
+            let time = self.time_of_day();
+            return !(0.25..0.75).contains(&time);
+        }
+
+        /// How dark the screen should be, fading in and out around dusk and dawn.
+        pub fn darkness(&self) -> f32 {
+            let distance_from_noon = (self.time_of_day() - 0.5).abs();
+            let fade = ((distance_from_noon - 0.2) / 0.1).clamp(0.0, 1.0);
+            return fade * NIGHT_DARKNESS;
+        }
+
+        pub fn vision_radius(&self, radius: i32) -> i32 {
+            if self.is_night() {
+                return (radius * NIGHT_VISION_PERCENT / 100).max(1);
+            }
+            return radius;
+        }
+
+        pub fn describe(&self) -> String {
+            let time = if self.is_night() { "Night" } else { "Day" };
+            return format!("Year {}, {} day {} ({})", self.year, self.season.name(), self.day, time);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+pub mod camera {
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::Position;
+    use legion::{Entity, World, IntoQuery};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum CameraMode {
+        Free,
+        Follow(Entity),
+    }
+
+    /// The part of the map shown on screen. The camera is always kept inside the centre copy
+    /// of the map so the viewport can be blitted straight out of the wrapped `con` console.
+    pub struct Camera {
+        pub x: i32,
+        pub y: i32,
+        pub width: i32,
+        pub height: i32,
+        map_width: i32,
+        map_height: i32,
+        pub mode: CameraMode,
+        pub followed_villager: usize,
+        pub visited_settlement: usize,
+        time_since_last_scroll: u128,
+    }
+
+    impl Camera {
+        pub const TIME_BETWEEN_EDGE_SCROLLS: u128 = 50;
+
+        pub fn new(width: i32, height: i32, map_width: i32, map_height: i32) -> Camera {
+            return Camera {
+                x: map_width + map_width / 2,
+                y: map_height + map_height / 2,
+                width,
+                height,
+                map_width,
+                map_height,
+                mode: CameraMode::Free,
+                followed_villager: 0,
+                visited_settlement: 0,
+                time_since_last_scroll: 0,
+            }
+        }
+
+        pub fn left(&self) -> i32 {
+            return self.x - (self.width / 2);
+        }
+
+        pub fn top(&self) -> i32 {
+            return self.y - (self.height / 2);
+        }
+
+        pub fn right(&self) -> i32 {
+            return self.x + (self.width / 2);
+        }
+
+        pub fn bottom(&self) -> i32 {
+            return self.y + (self.height / 2);
+        }
+
+        pub fn centre_on(&mut self, x: i32, y: i32) {
+            self.x = x.rem_euclid(self.map_width) + self.map_width;
+            self.y = y.rem_euclid(self.map_height) + self.map_height;
+        }
+
+        /// Scrolls the camera freely, detaching it from whatever it was following.
+        pub fn scroll(&mut self, x_delta: i32, y_delta: i32) {
+            self.mode = CameraMode::Free;
+            self.centre_on(self.x + x_delta, self.y + y_delta);
+        }
+
+        pub fn jump_to(&mut self, x: i32, y: i32) {
+            self.mode = CameraMode::Free;
+            self.centre_on(x, y);
+        }
+
+        pub fn follow(&mut self, entity: Entity) {
+            self.mode = CameraMode::Follow(entity);
+        }
+
+        /// Scrolls towards any screen edge the mouse is resting on. The panel covers the top
+        /// of the screen, so the top edge is the first row of map below it, and the mouse
+        /// over the panel itself does not scroll.
+        pub fn edge_scroll(&mut self, mouse_x: i32, mouse_y: i32, time_delta: u128) {
+            let x_delta = if mouse_x <= 0 { -1 } else if mouse_x >= self.width - 1 { 1 } else { 0 };
+            let y_delta = if mouse_y == PANEL_HEIGHT { -1 } else if mouse_y >= self.height - 1 { 1 } else { 0 };
+            if mouse_y < PANEL_HEIGHT || (x_delta == 0 && y_delta == 0) {
+                self.time_since_last_scroll = 0;
+                return;
+            }
+            self.time_since_last_scroll += time_delta;
+            if self.time_since_last_scroll >= Camera::TIME_BETWEEN_EDGE_SCROLLS {
+                self.time_since_last_scroll = 0;
+                self.scroll(x_delta * CAMERA_SCROLL_SPEED, y_delta * CAMERA_SCROLL_SPEED);
+            }
+        }
+
+        /// Moves the camera onto the followed entity, falling back to free mode if it is gone.
+        pub fn update(&mut self, world: &World) {
+            if let CameraMode::Follow(entity) = self.mode {
+                let mut query = <&Position>::query();
+                match query.get(world, entity) {
+                    Ok(position) => {
+                        let (x, y) = (position.x, position.y);
+                        self.centre_on(x, y);
+                    }
+                    Err(_) => self.mode = CameraMode::Free,
+                }
+            }
+        }
+    }
+}
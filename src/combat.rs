@@ -0,0 +1,199 @@
+pub mod combat {
+    use std::collections::HashMap;
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::{Faction, Game, Person, Player, Position, remove_person};
+    use crate::wildlife::wildlife::Animal;
+    use crate::raiders::raiders::Raider;
+    use crate::messages::messages::capitalise;
+    use rand::Rng;
+    use rand::rngs::StdRng;
+    use legion::{Entity, World, IntoQuery};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Health {
+        pub current: i32,
+        pub max: i32,
+    }
+
+    impl Health {
+        pub fn new(max: i32) -> Health {
+            return Health { current: max, max }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Attack {
+        pub power: i32,
+        pub time_since_last_attack: u128,
+    }
+
+    impl Attack {
+        pub const TIME_BETWEEN_ATTACKS: u128 = 1000;
+
+        pub fn new(power: i32) -> Attack {
+            return Attack { power, time_since_last_attack: 0 }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Defense {
+        pub armour: i32,
+    }
+
+    impl Defense {
+        pub fn new(armour: i32) -> Defense {
+            return Defense { armour }
+        }
+    }
+
+    /// Which side of a fight something is on. Wild animals and raiders fight everyone who
+    /// lives in a settlement but leave each other be, settlements only fight those they are
+    /// hostile with.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Side {
+        Wild,
+        Raider,
+        Settlement(Faction),
+    }
+
+    struct Combatant {
+        entity: Entity,
+        position: Position,
+        side: Side,
+        name: String,
+        armour: i32,
+    }
+
+    /// Gives an entity the components it needs to fight and be hurt.
+    pub(crate) fn arm(world: &mut World, entity: Entity, health: i32, power: i32, armour: i32) {
+        if let Some(mut entry) = world.entry(entity) {
+            entry.add_component(Health::new(health));
+            entry.add_component(Attack::new(power));
+            entry.add_component(Defense::new(armour));
+        }
+    }
+
+    fn combatant(entity: Entity, position: Position, defense: Option<&Defense>, side: Side, name: String) -> Combatant {
+        let armour = defense.map_or(0, |defense| defense.armour);
+        return Combatant { entity, position, side, name, armour };
+    }
+
+    fn combatants(game: &Game) -> Vec<Combatant> {
+        let mut fighters = Vec::new();
+        let mut player_query = <(Entity, &Position, &Health, Option<&Defense>, &Player)>::query();
+        for (entity, position, health, defense, _) in player_query.iter(&game.world) {
+            if health.current > 0 {
+                let side = Side::Settlement(Faction::PLAYER);
+                fighters.push(combatant(*entity, *position, defense, side, String::from("you")));
+            }
+        }
+        let mut person_query = <(Entity, &Position, &Health, Option<&Defense>, &Person, &Faction)>::query();
+        for (entity, position, health, defense, person, faction) in person_query.iter(&game.world) {
+            if health.current > 0 {
+                let side = Side::Settlement(*faction);
+                fighters.push(combatant(*entity, *position, defense, side, person.name.clone()));
+            }
+        }
+        let mut animal_query = <(Entity, &Position, &Health, Option<&Defense>, &Animal)>::query();
+        for (entity, position, health, defense, animal) in animal_query.iter(&game.world) {
+            if health.current > 0 {
+                let name = format!("a {}", animal.species.name());
+                fighters.push(combatant(*entity, *position, defense, Side::Wild, name));
+            }
+        }
+        let mut raider_query = <(Entity, &Position, &Health, Option<&Defense>, &Raider)>::query();
+        for (entity, position, health, defense, _) in raider_query.iter(&game.world) {
+            if health.current > 0 {
+                fighters.push(combatant(*entity, *position, defense, Side::Raider, String::from("a raider")));
+            }
+        }
+        return fighters;
+    }
+
+    /// Everything that can attack strikes an adjacent enemy once its attack is ready. Damage
+    /// is a roll of between half and all of the attacker's power, less the target's armour.
+    pub(crate) fn combat_system(game: &mut Game, rng: &mut StdRng, time_delta: u128) {
+        let fighters = combatants(game);
+        let index_of: HashMap<Entity, usize> = fighters.iter()
+            .enumerate()
+            .map(|(index, fighter)| (fighter.entity, index))
+            .collect();
+        let settlements = &game.settlements;
+        let is_enemy = |a: Side, b: Side| match (a, b) {
+            (Side::Settlement(a), Side::Settlement(b)) => settlements.is_hostile(a, b),
+            (Side::Settlement(_), _) | (_, Side::Settlement(_)) => true,
+            _ => false,
+        };
+
+        let mut hits = Vec::new();
+        let mut query = <(Entity, &mut Attack)>::query();
+        for (entity, attack) in query.iter_mut(&mut game.world) {
+            attack.time_since_last_attack += time_delta;
+            if attack.power <= 0 || attack.time_since_last_attack < Attack::TIME_BETWEEN_ATTACKS {
+                continue;
+            }
+            let attacker = match index_of.get(entity) {
+                Some(index) => &fighters[*index],
+                None => continue,
+            };
+            let target = fighters.iter()
+                .filter(|target| target.entity != attacker.entity && is_enemy(attacker.side, target.side))
+                .find(|target| attacker.position.distance(target.position) <= 1);
+            if let Some(target) = target {
+                attack.time_since_last_attack = 0;
+                let roll = rng.gen_range(attack.power / 2, attack.power + 1);
+                hits.push((index_of[entity], index_of[&target.entity], (roll - target.armour).max(0)));
+            }
+        }
+
+        let mut deaths = Vec::new();
+        for (attacker, target, damage) in hits {
+            let (attacker, target) = (&fighters[attacker], &fighters[target]);
+            if damage == 0 {
+                game.messages.add(capitalise(&format!("{} misses {}", attacker.name, target.name)), COLOR_COMBAT);
+                continue;
+            }
+            let mut query = <&mut Health>::query();
+            if let Ok(health) = query.get_mut(&mut game.world, target.entity) {
+                if health.current <= 0 {
+                    continue;
+                }
+                health.current -= damage;
+                game.messages.add(capitalise(&format!("{} hits {} for {}", attacker.name, target.name, damage)), COLOR_COMBAT);
+                if health.current <= 0 {
+                    deaths.push(target.entity);
+                    game.messages.add(capitalise(&format!("{} killed by {}", target.name, attacker.name)), COLOR_DEATH);
+                }
+            }
+        }
+        for entity in deaths {
+            kill(&mut game.world, entity);
+        }
+    }
+
+    /// Removes the dead from the world, taking villagers out of their houses. The player's
+    /// body stays where it fell, marked as no longer alive.
+    pub(crate) fn kill(world: &mut World, entity: Entity) {
+        let mut player_query = <&mut Player>::query();
+        if let Ok(player) = player_query.get_mut(world, entity) {
+            player.alive = false;
+            return;
+        }
+        let mut person_query = <&Person>::query();
+        if person_query.get(world, entity).is_ok() {
+            remove_person(world, entity);
+        } else {
+            world.remove(entity);
+        }
+    }
+
+    /// Wounds heal a little every day.
+    pub(crate) fn healing_system(world: &mut World) {
+        let mut query = <&mut Health>::query();
+        for health in query.iter_mut(world) {
+            if health.current > 0 {
+                health.current = (health.current + HEAL_PER_DAY).min(health.max);
+            }
+        }
+    }
+}
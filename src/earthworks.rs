@@ -0,0 +1,152 @@
+pub mod earthworks {
+    use std::collections::{HashMap, HashSet};
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::{Drawable, Faction, Game, GameMap, Job, Player, Position, Terrain};
+    use crate::research::research::{Improvement, Unlock};
+    use crate::messages::messages::capitalise;
+    use crate::resources::resources::Inventory;
+    use crate::staffing::staffing::{jobs, staff};
+    use crate::stockpile::stockpile::{store_goods, take_resources};
+    use legion::{Entity, IntoQuery};
+
+    /// A change to the land the player has ordered. Labourers put their time into it until
+    /// the work is done, then the tile is changed for good.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Earthwork {
+        pub improvement: Improvement,
+        pub progress: u128,
+        pub labourers: Vec<Entity>,
+    }
+
+    impl Earthwork {
+        pub fn new(improvement: Improvement) -> Earthwork {
+            return Earthwork { improvement, progress: 0, labourers: Vec::new() }
+        }
+    }
+
+    fn work_time(improvement: Improvement) -> u128 {
+        return match improvement {
+            Improvement::ClearForest => CLEAR_FOREST_TIME,
+            Improvement::Road => ROAD_TIME,
+            Improvement::Irrigation => IRRIGATION_TIME,
+            Improvement::Tunnel => TUNNEL_TIME,
+        };
+    }
+
+    /// Whether the improvement can be made to a tile. Irrigation channels have to be fed from
+    /// open water or another channel.
+    fn can_improve(map: &GameMap, improvement: Improvement, x: i32, y: i32) -> bool {
+        let tile = map.get_tile(x as usize, y as usize);
+        if tile.is_built_on() || map.is_road(x, y) || map.is_channel(x, y) {
+            return false;
+        }
+        return match improvement {
+            Improvement::ClearForest => tile.terrain == Terrain::Forest,
+            Improvement::Road => !tile.is_blocked() && tile.terrain != Terrain::Water,
+            Improvement::Irrigation => {
+                tile.terrain == Terrain::Meadow && !tile.is_blocked() && [(0, -1), (0, 1), (-1, 0), (1, 0)].iter()
+                    .any(|(x_offset, y_offset)| map.is_water(x + x_offset, y + y_offset) || map.is_channel(x + x_offset, y + y_offset))
+            }
+            Improvement::Tunnel => tile.terrain == Terrain::Hill && tile.is_blocked(),
+        };
+    }
+
+    /// Marks out an earthwork on the player's tile or, failing that, a neighbouring one.
+    pub(crate) fn order_earthwork(game: &mut Game, improvement: Improvement) {
+        if !game.research.is_unlocked(Unlock::Improvement(improvement)) {
+            game.messages.add(format!("{} has not been researched yet", capitalise(improvement.name())), COLOR_UNAFFORDABLE);
+            return;
+        }
+        let mut query = <(&Player, &Position)>::query();
+        let player = *query.iter(&game.world).next().unwrap().1;
+        let mut query = <(&Earthwork, &Position)>::query();
+        let planned: HashSet<(i32, i32)> = query.iter(&game.world).map(|(_, position)| (position.x, position.y)).collect();
+        let site = [(0, 0), (0, -1), (0, 1), (-1, 0), (1, 0)].iter()
+            .map(|(x_offset, y_offset)| Position::new(player.x + x_offset, player.y + y_offset))
+            .filter(|site| !planned.contains(&(site.x, site.y)))
+            .find(|site| can_improve(&game.map, improvement, site.x, site.y));
+        let site = match site {
+            Some(site) => site,
+            None => {
+                game.messages.add(format!("There is nowhere here for {}", improvement.name()), COLOR_UNAFFORDABLE);
+                return;
+            }
+        };
+        if improvement == Improvement::Road {
+            let cost = vec![(game.resources.id(ROAD_MATERIAL), ROAD_COST)];
+            if !take_resources(&mut game.world, Faction::PLAYER, site, &cost) {
+                return;
+            }
+        }
+        game.world.push((site, Drawable::new('x', COLOR_EARTHWORK), Earthwork::new(improvement), Faction::PLAYER));
+    }
+
+    /// Puts the labourers' time into their earthworks and changes the land once one is done.
+    pub(crate) fn dig(game: &mut Game, labour: HashMap<Entity, u128>) {
+        let mut finished = Vec::new();
+        let mut query = <(Entity, &mut Earthwork, &Position)>::query();
+        for (entity, earthwork, position) in query.iter_mut(&mut game.world) {
+            if let Some(time) = labour.get(entity) {
+                earthwork.progress += time;
+                if earthwork.progress >= work_time(earthwork.improvement) {
+                    finished.push((*entity, earthwork.improvement, *position));
+                }
+            }
+        }
+        for (entity, improvement, position) in finished {
+            game.world.remove(entity);
+            let (x, y) = (position.x, position.y);
+            match improvement {
+                Improvement::ClearForest => {
+                    game.map.clear_forest(x, y);
+                    let wood = game.resources.id(BUILDING_MATERIAL);
+                    store_goods(game, position, &[(wood, CLEARING_YIELD)]);
+                    game.messages.add(String::from("A stretch of forest has been cleared"), COLOR_EARTHWORK);
+                }
+                Improvement::Road => {
+                    game.map.build_road(x, y);
+                    game.messages.add(String::from("A road has been laid"), COLOR_EARTHWORK);
+                }
+                Improvement::Irrigation => {
+                    game.map.dig_channel(x, y);
+                    for y in y - IRRIGATION_RADIUS..=y + IRRIGATION_RADIUS {
+                        for x in x - IRRIGATION_RADIUS..=x + IRRIGATION_RADIUS {
+                            if game.map.terrain(x, y) == Terrain::Meadow && !game.map.is_channel(x, y) && !game.map.is_road(x, y) {
+                                game.map.change_fertility(x, y, IRRIGATION_FERTILITY);
+                            }
+                        }
+                    }
+                    game.messages.add(String::from("An irrigation channel has been dug"), COLOR_EARTHWORK);
+                }
+                Improvement::Tunnel => {
+                    game.map.tunnel(x, y);
+                    let stone = game.resources.id(ROAD_MATERIAL);
+                    store_goods(game, position, &[(stone, TUNNEL_YIELD)]);
+                    game.messages.add(String::from("A tunnel has been dug through the hill"), COLOR_EARTHWORK);
+                }
+            }
+        }
+    }
+
+    /// Puts labourers on every unfinished earthwork. Labourers whose earthwork is finished
+    /// go back to ordinary work.
+    pub(crate) fn staff_earthworks(game: &mut Game, stocks: &HashMap<Faction, Inventory>) {
+        let jobs = jobs(&game.world);
+        let mut standing = HashSet::new();
+        let mut vacancies = Vec::new();
+        let mut earthwork_query = <(Entity, &mut Earthwork)>::query();
+        for (entity, earthwork) in earthwork_query.iter_mut(&mut game.world) {
+            earthwork.labourers.retain(|labourer| jobs.get(labourer) == Some(&Some(Job::Labourer(*entity))));
+            standing.insert(*entity);
+            vacancies.extend(std::iter::repeat_n((*entity, Faction::PLAYER), LABOURERS_PER_EARTHWORK - earthwork.labourers.len()));
+        }
+
+        let staffing = staff(game, stocks, &standing, vacancies, Job::Labourer);
+        let mut earthwork_query = <&mut Earthwork>::query();
+        for (earthwork, labourer) in staffing.hired {
+            if let Ok(earthwork) = earthwork_query.get_mut(&mut game.world, earthwork) {
+                earthwork.labourers.push(labourer);
+            }
+        }
+    }
+}
@@ -0,0 +1,402 @@
+pub mod events {
+    use std::collections::{HashMap, HashSet};
+    use std::fs;
+    use serde::Deserialize;
+    use crate::settings::settings::*;
+    use crate::ai::ai::Intent;
+    use crate::calendar::calendar::Season;
+    use crate::combat::combat::{arm, kill};
+    use crate::game_objects::game_objects::{Drawable, Faction, Game, House, Person, Player, Position};
+    use crate::names::names::generate_name;
+    use crate::raiders::raiders::spawn_band;
+    use crate::resources::resources::{ResourceId, ResourceRegistry};
+    use crate::stockpile::stockpile::{store_goods, take_resources, total_stock};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use legion::{Entity, IntoQuery};
+
+    pub const EVENTS_PATH: &str = "data/events.ron";
+
+    #[derive(Deserialize)]
+    enum ConditionDefinition {
+        ResourceBelow(String, i32),
+        ResourceAbove(String, i32),
+        FoodBelow(i32),
+        Season(Season),
+        PopulationAtLeast(i32),
+        PopulationBelow(i32),
+        DaysElapsed(i32),
+    }
+
+    #[derive(Deserialize)]
+    enum EffectDefinition {
+        Resource(String, i32),
+        Settlers(i32),
+        Deaths(i32),
+        Relations(i32),
+        Fertility(i32),
+        Raid(i32),
+    }
+
+    #[derive(Deserialize)]
+    struct ChoiceDefinition {
+        label: String,
+        effects: Vec<EffectDefinition>,
+    }
+
+    #[derive(Deserialize)]
+    struct EventDefinition {
+        title: String,
+        text: String,
+        conditions: Vec<ConditionDefinition>,
+        chance: i32,
+        cooldown: i32,
+        choices: Vec<ChoiceDefinition>,
+    }
+
+    #[derive(Deserialize)]
+    struct EventFile {
+        events: Vec<EventDefinition>,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Condition {
+        ResourceBelow(ResourceId, i32),
+        ResourceAbove(ResourceId, i32),
+        FoodBelow(i32),
+        Season(Season),
+        PopulationAtLeast(i32),
+        PopulationBelow(i32),
+        DaysElapsed(i32),
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Effect {
+        Resource(ResourceId, i32),
+        Settlers(i32),
+        Deaths(i32),
+        Relations(i32),
+        Fertility(i32),
+        Raid(i32),
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Choice {
+        pub label: String,
+        pub effects: Vec<Effect>,
+    }
+
+    impl Choice {
+        /// The resources the choice takes from the stockpiles.
+        pub fn cost(&self) -> Vec<(ResourceId, i32)> {
+            return self.effects.iter()
+                .filter_map(|effect| match effect {
+                    Effect::Resource(id, amount) if *amount < 0 => Some((*id, -amount)),
+                    _ => None,
+                })
+                .collect();
+        }
+
+        pub fn describe(&self, resources: &ResourceRegistry) -> String {
+            let effects: Vec<String> = self.effects.iter()
+                .map(|effect| match effect {
+                    Effect::Resource(id, amount) => format!("{:+} {}", amount, resources.get(*id).name),
+                    Effect::Settlers(count) => format!("+{} settlers", count),
+                    Effect::Deaths(count) => format!("{} deaths", count),
+                    Effect::Relations(change) => format!("{:+} relations", change),
+                    Effect::Fertility(change) => format!("{:+} fertility", change),
+                    Effect::Raid(size) => format!("raid of {}", size),
+                })
+                .collect();
+            return if effects.is_empty() { self.label.clone() } else { format!("{} ({})", self.label, effects.join(", ")) };
+        }
+    }
+
+    /// A narrative event with its resource names resolved to ids.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct NarrativeEvent {
+        pub title: String,
+        pub text: String,
+        pub conditions: Vec<Condition>,
+        pub chance: i32,
+        pub cooldown: i32,
+        pub choices: Vec<Choice>,
+    }
+
+    /// Every narrative event, loaded from `EVENTS_PATH`, and the one waiting on the player's
+    /// choice if there is one.
+    pub struct Events {
+        rng: StdRng,
+        pub book: Vec<NarrativeEvent>,
+        /// The day each event last happened.
+        last_seen: HashMap<usize, i32>,
+        pub pending: Option<usize>,
+    }
+
+    impl Events {
+        pub fn load(resources: &ResourceRegistry, seed: u64) -> Events {
+            let text = fs::read_to_string(EVENTS_PATH)
+                .unwrap_or_else(|error| panic!("Could not read {}: {}", EVENTS_PATH, error));
+            return Events::parse(&text, resources, seed);
+        }
+
+        pub fn parse(text: &str, resources: &ResourceRegistry, seed: u64) -> Events {
+            let file: EventFile = ron::de::from_str(text)
+                .unwrap_or_else(|error| panic!("Could not parse {}: {}", EVENTS_PATH, error));
+            let book = file.events.iter()
+                .map(|event| NarrativeEvent {
+                    title: event.title.clone(),
+                    text: event.text.clone(),
+                    conditions: event.conditions.iter()
+                        .map(|condition| match condition {
+                            ConditionDefinition::ResourceBelow(id, amount) => Condition::ResourceBelow(resources.id(id), *amount),
+                            ConditionDefinition::ResourceAbove(id, amount) => Condition::ResourceAbove(resources.id(id), *amount),
+                            ConditionDefinition::FoodBelow(amount) => Condition::FoodBelow(*amount),
+                            ConditionDefinition::Season(season) => Condition::Season(*season),
+                            ConditionDefinition::PopulationAtLeast(count) => Condition::PopulationAtLeast(*count),
+                            ConditionDefinition::PopulationBelow(count) => Condition::PopulationBelow(*count),
+                            ConditionDefinition::DaysElapsed(days) => Condition::DaysElapsed(*days),
+                        })
+                        .collect(),
+                    chance: event.chance,
+                    cooldown: event.cooldown,
+                    choices: event.choices.iter()
+                        .map(|choice| Choice {
+                            label: choice.label.clone(),
+                            effects: choice.effects.iter()
+                                .map(|effect| match effect {
+                                    EffectDefinition::Resource(id, amount) => Effect::Resource(resources.id(id), *amount),
+                                    EffectDefinition::Settlers(count) => Effect::Settlers(*count),
+                                    EffectDefinition::Deaths(count) => Effect::Deaths(*count),
+                                    EffectDefinition::Relations(change) => Effect::Relations(*change),
+                                    EffectDefinition::Fertility(change) => Effect::Fertility(*change),
+                                    EffectDefinition::Raid(size) => Effect::Raid(*size),
+                                })
+                                .collect(),
+                        })
+                        .collect(),
+                })
+                .collect();
+            return Events { rng: StdRng::seed_from_u64(seed), book, last_seen: HashMap::new(), pending: None };
+        }
+
+        pub fn pending_event(&self) -> Option<&NarrativeEvent> {
+            return self.pending.map(|index| &self.book[index]);
+        }
+    }
+
+    fn holds(game: &Game, condition: Condition) -> bool {
+        return match condition {
+            Condition::ResourceBelow(id, amount) => game.stock.get(id) < amount,
+            Condition::ResourceAbove(id, amount) => game.stock.get(id) >= amount,
+            Condition::FoodBelow(amount) => {
+                game.resources.edible().iter().map(|id| game.stock.get(*id)).sum::<i32>() < amount
+            }
+            Condition::Season(season) => game.calendar.season == season,
+            Condition::PopulationAtLeast(count) => game.population >= count,
+            Condition::PopulationBelow(count) => game.population < count,
+            Condition::DaysElapsed(days) => game.calendar.days_elapsed >= days,
+        };
+    }
+
+    pub(crate) fn can_afford(game: &Game, choice: &Choice) -> bool {
+        return game.stock.has_all(&choice.cost());
+    }
+
+    /// Rolls for each event whose conditions hold, once a day, and holds the first to come up
+    /// until the player has chosen what to do about it. Events none of whose choices the
+    /// colony could pay for are passed over so the game can never get stuck on one.
+    pub(crate) fn event_system(game: &mut Game) {
+        if game.events.pending.is_some() {
+            return;
+        }
+        let today = game.calendar.days_elapsed;
+        for index in 0..game.events.book.len() {
+            let event = &game.events.book[index];
+            let rested = game.events.last_seen.get(&index).is_none_or(|day| today - day >= event.cooldown);
+            let possible = event.conditions.iter().all(|condition| holds(game, *condition))
+                && event.choices.iter().any(|choice| can_afford(game, choice));
+            if !rested || !possible {
+                continue;
+            }
+            let chance = event.chance;
+            if game.events.rng.gen_range(0, 100) < chance {
+                game.events.last_seen.insert(index, today);
+                game.events.pending = Some(index);
+                return;
+            }
+        }
+    }
+
+    /// Applies the chosen option of the pending event, returning false if the colony cannot
+    /// pay for it.
+    pub(crate) fn choose(game: &mut Game, option: usize) -> bool {
+        let choice = match game.events.pending_event().and_then(|event| event.choices.get(option)) {
+            Some(choice) => choice.clone(),
+            None => return false,
+        };
+        let centre = match colony_centre(game) {
+            Some(centre) => centre,
+            None => return false,
+        };
+        if !take_resources(&mut game.world, Faction::PLAYER, centre, &choice.cost()) {
+            return false;
+        }
+        for effect in choice.effects.iter() {
+            match *effect {
+                Effect::Resource(id, amount) if amount > 0 => store_goods(game, centre, &[(id, amount)]),
+                Effect::Resource(_, _) => {}
+                Effect::Settlers(count) => settle(game, centre, count),
+                Effect::Deaths(count) => kill_villagers(game, count),
+                Effect::Relations(change) => {
+                    let factions: Vec<Faction> = game.settlements.rivals.iter().map(|rival| rival.faction).collect();
+                    for faction in factions {
+                        game.settlements.change_relation(faction, change);
+                    }
+                }
+                Effect::Fertility(change) => change_fertility(game, change),
+                Effect::Raid(size) => spawn_band(game, size),
+            }
+        }
+        game.stock = total_stock(&game.world, Faction::PLAYER);
+        game.events.pending = None;
+        return true;
+    }
+
+    /// Where the colony's effects are centred: its first house, or the player before any
+    /// house is built.
+    fn colony_centre(game: &Game) -> Option<Position> {
+        let mut house_query = <(&House, &Position, &Faction)>::query();
+        let house = house_query.iter(&game.world)
+            .find(|(_, _, faction)| **faction == Faction::PLAYER)
+            .map(|(_, position, _)| *position);
+        let mut player_query = <(&Player, &Position)>::query();
+        return house.or_else(|| player_query.iter(&game.world).next().map(|(_, position)| *position));
+    }
+
+    /// Adults arrive homeless and move into the first house with room for them.
+    fn settle(game: &mut Game, centre: Position, count: i32) {
+        for _ in 0..count {
+            let spot = (0..20).find_map(|_| {
+                let x = centre.x + game.events.rng.gen_range(-3, 4);
+                let y = centre.y + game.events.rng.gen_range(-3, 4);
+                if game.map.is_buildable(x, y) { Some(Position::new(x, y)) } else { None }
+            });
+            let spot = match spot {
+                Some(spot) => spot,
+                None => continue,
+            };
+            let person = Person::new(None, generate_name(&mut game.events.rng), ADULT_AGE * DAYS_PER_YEAR);
+            game.messages.add(format!("{} has joined the colony", person.name), COLOR_PERSON);
+            let drawable = Drawable::new('&', COLOR_PERSON);
+            let entity = game.world.push((person, spot, drawable, Intent::new(), Faction::PLAYER));
+            arm(&mut game.world, entity, PERSON_HEALTH, PERSON_ATTACK, 0);
+        }
+    }
+
+    fn kill_villagers(game: &mut Game, count: i32) {
+        let mut query = <(Entity, &Person, &Faction)>::query();
+        let mut villagers: Vec<(Entity, String)> = query.iter(&game.world)
+            .filter(|(_, _, faction)| **faction == Faction::PLAYER)
+            .map(|(entity, person, _)| (*entity, person.name.clone()))
+            .collect();
+        for _ in 0..count {
+            if villagers.is_empty() {
+                return;
+            }
+            let (villager, name) = villagers.remove(game.events.rng.gen_range(0, villagers.len()));
+            game.messages.add(format!("{} has died", name), COLOR_DEATH);
+            kill(&mut game.world, villager);
+        }
+    }
+
+    /// Changes the fertility of the fields within working distance of the colony's houses.
+    fn change_fertility(game: &mut Game, change: i32) {
+        let mut query = <(&House, &Position, &Faction)>::query();
+        let houses: Vec<Position> = query.iter(&game.world)
+            .filter(|(_, _, faction)| **faction == Faction::PLAYER)
+            .map(|(_, position, _)| *position)
+            .collect();
+        let mut fields = HashSet::new();
+        for house in houses {
+            for y in house.y - WORK_RADIUS..=house.y + WORK_RADIUS {
+                for x in house.x - WORK_RADIUS..=house.x + WORK_RADIUS {
+                    if game.map.get_tile(x as usize, y as usize).fertility > 0 {
+                        fields.insert((x, y));
+                    }
+                }
+            }
+        }
+        for (x, y) in fields {
+            game.map.change_fertility(x, y, change);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn event_file_is_resolved_against_the_resources() {
+            let resources = ResourceRegistry::load();
+            let events = Events::load(&resources, 0);
+            let granaries = events.book.iter().find(|event| event.title == "Empty granaries").unwrap();
+            assert_eq!(granaries.conditions, vec![Condition::FoodBelow(30), Condition::PopulationAtLeast(3)]);
+            assert_eq!(granaries.choices.len(), 3);
+            assert_eq!(granaries.choices[0].effects, vec![
+                Effect::Resource(resources.id("wood"), -30),
+                Effect::Resource(resources.id("grain"), 25),
+                Effect::Relations(5),
+            ]);
+            assert!(events.book.iter().all(|event| !event.choices.is_empty()));
+        }
+
+        #[test]
+        fn events_are_parsed() {
+            let resources = ResourceRegistry::parse(r#"(
+                resources: [
+                    (id: "wood", name: "Wood", method: Gather, sources: [Forest]),
+                ],
+            )"#);
+            let events = Events::parse(r#"(
+                events: [
+                    (
+                        title: "Storm",
+                        text: "A storm blows through the colony.",
+                        conditions: [Season(Autumn), ResourceAbove("wood", 10)],
+                        chance: 5,
+                        cooldown: 30,
+                        choices: [(label: "Repair the roofs", effects: [Resource("wood", -10), Deaths(1)])],
+                    ),
+                ],
+            )"#, &resources, 0);
+            assert_eq!(events.book, vec![NarrativeEvent {
+                title: String::from("Storm"),
+                text: String::from("A storm blows through the colony."),
+                conditions: vec![Condition::Season(Season::Autumn), Condition::ResourceAbove(ResourceId(0), 10)],
+                chance: 5,
+                cooldown: 30,
+                choices: vec![Choice {
+                    label: String::from("Repair the roofs"),
+                    effects: vec![Effect::Resource(ResourceId(0), -10), Effect::Deaths(1)],
+                }],
+            }]);
+        }
+
+        #[test]
+        #[should_panic(expected = "Could not parse")]
+        fn malformed_event_file_panics() {
+            Events::parse(r#"(events: [(title: "Storm", chance: 5)])"#, &ResourceRegistry::load(), 0);
+        }
+
+        #[test]
+        #[should_panic(expected = "Unknown resource 'gold'")]
+        fn unknown_resource_in_event_panics() {
+            Events::parse(r#"(
+                events: [
+                    (title: "Gold", text: "", conditions: [ResourceBelow("gold", 1)], chance: 5, cooldown: 30, choices: []),
+                ],
+            )"#, &ResourceRegistry::load(), 0);
+        }
+    }
+}
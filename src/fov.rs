@@ -0,0 +1,310 @@
+pub mod fov {
+    use std::collections::HashSet;
+    use crate::game_objects::game_objects::GameMap;
+
+    /// How generous a field of view is. Shadowcasting lights open ground only when the
+    /// centre of the tile can be seen, permissive lights anything of which any part can be
+    /// seen, so it looks further around corners and past the edges of obstacles.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum FovAlgorithm {
+        Shadowcasting,
+        Permissive,
+    }
+
+    /// Turns a position within the first octant into one within each of the eight.
+    const OCTANTS: [(i32, i32, i32, i32); 8] = [
+        (1, 0, 0, 1),
+        (0, 1, 1, 0),
+        (0, -1, 1, 0),
+        (-1, 0, 0, 1),
+        (-1, 0, 0, -1),
+        (0, -1, -1, 0),
+        (0, 1, -1, 0),
+        (1, 0, 0, -1),
+    ];
+
+    struct Scan<'a> {
+        map: &'a GameMap,
+        origin: (i32, i32),
+        radius: i32,
+        light_walls: bool,
+        permissive: bool,
+        visible: HashSet<(i32, i32)>,
+    }
+
+    impl<'a> Scan<'a> {
+        /// Recursive shadowcasting over one octant, row by row outwards from the origin.
+        /// `start` and `end` are the slopes bounding the part of the row still in view, and
+        /// every opaque tile narrows the view of the rows beyond it.
+        fn cast_light(&mut self, row: i32, start: f64, end: f64, octant: (i32, i32, i32, i32)) {
+            if start < end {
+                return;
+            }
+            let (xx, xy, yx, yy) = octant;
+            let mut start = start;
+            let mut new_start = 0.0;
+            for distance in row..=self.radius {
+                let dy = -distance;
+                let mut blocked = false;
+                for dx in -distance..=0 {
+                    let x = self.origin.0 + dx * xx + dy * xy;
+                    let y = self.origin.1 + dx * yx + dy * yy;
+                    let left_slope = (dx as f64 - 0.5) / (dy as f64 + 0.5);
+                    let right_slope = (dx as f64 + 0.5) / (dy as f64 - 0.5);
+                    if start < right_slope {
+                        continue;
+                    }
+                    if end > left_slope {
+                        break;
+                    }
+                    let opaque = self.map.blocks_sight(x, y);
+                    if dx * dx + dy * dy <= self.radius * self.radius {
+                        let centre = dx as f64 / dy as f64;
+                        let seen = if opaque {
+                            self.light_walls
+                        } else {
+                            self.permissive || (centre <= start && centre >= end)
+                        };
+                        if seen {
+                            self.visible.insert((x, y));
+                        }
+                    }
+                    if blocked {
+                        if opaque {
+                            new_start = right_slope;
+                        } else {
+                            blocked = false;
+                            start = new_start;
+                        }
+                    } else if opaque && distance < self.radius {
+                        blocked = true;
+                        self.cast_light(distance + 1, start, left_slope, octant);
+                        new_start = right_slope;
+                    }
+                }
+                if blocked {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The tiles that can be seen from a position within a circle of the given radius. The
+    /// map wraps around, so tiles past its edges are returned in the coordinates they were
+    /// seen at rather than folded back onto the map.
+    pub(crate) fn compute_fov(map: &GameMap, x: i32, y: i32, radius: i32, light_walls: bool, algorithm: FovAlgorithm) -> HashSet<(i32, i32)> {
+        let mut scan = Scan {
+            map,
+            origin: (x, y),
+            radius,
+            light_walls,
+            permissive: algorithm == FovAlgorithm::Permissive,
+            visible: HashSet::new(),
+        };
+        scan.visible.insert((x, y));
+        for octant in OCTANTS.iter() {
+            scan.cast_light(1, 1.0, 0.0, *octant);
+        }
+        return scan.visible;
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::test_maps::test_maps::map_from;
+
+        /// Draws what was seen over the fixture, folding tiles seen past the edges back onto
+        /// the map. Tiles that were not seen are drawn as `-`.
+        fn render(rows: &[&str], visible: &HashSet<(i32, i32)>) -> Vec<String> {
+            let (width, height) = (rows[0].len() as i32, rows.len() as i32);
+            let seen: HashSet<(i32, i32)> = visible.iter()
+                .map(|(x, y)| (x.rem_euclid(width), y.rem_euclid(height)))
+                .collect();
+            return rows.iter().enumerate()
+                .map(|(y, row)| {
+                    row.chars().enumerate()
+                        .map(|(x, cell)| if seen.contains(&(x as i32, y as i32)) { cell } else { '-' })
+                        .collect()
+                })
+                .collect();
+        }
+
+        fn check(rows: &[&str], radius: i32, algorithm: FovAlgorithm, expected: &[&str]) {
+            let (map, (x, y)) = map_from(rows);
+            let visible = compute_fov(&map, x, y, radius, true, algorithm);
+            assert_eq!(render(rows, &visible), expected);
+        }
+
+        #[test]
+        fn open_ground_is_seen_within_a_circle() {
+            let rows = [
+                ".......",
+                ".......",
+                ".......",
+                "...@...",
+                ".......",
+                ".......",
+                ".......",
+            ];
+            let expected = [
+                "---.---",
+                "-.....-",
+                "-.....-",
+                "...@...",
+                "-.....-",
+                "-.....-",
+                "---.---",
+            ];
+            check(&rows, 3, FovAlgorithm::Shadowcasting, &expected);
+            check(&rows, 3, FovAlgorithm::Permissive, &expected);
+        }
+        #[test]
+        fn a_pillar_casts_a_shadow() {
+            let rows = [
+                ".............",
+                ".............",
+                ".............",
+                ".............",
+                "......#......",
+                ".............",
+                "......@......",
+                ".............",
+                ".............",
+                ".............",
+                ".............",
+                ".............",
+                ".............",
+            ];
+            check(&rows, 5, FovAlgorithm::Shadowcasting, &[
+                "-------------",
+                "-------------",
+                "---..---..---",
+                "--....-....--",
+                "--....#....--",
+                "--.........--",
+                "-.....@.....-",
+                "--.........--",
+                "--.........--",
+                "--.........--",
+                "---.......---",
+                "------.------",
+                "-------------",
+            ]);
+            check(&rows, 5, FovAlgorithm::Permissive, &[
+                "-------------",
+                "-------------",
+                "---...-...---",
+                "--....-....--",
+                "--....#....--",
+                "--.........--",
+                "-.....@.....-",
+                "--.........--",
+                "--.........--",
+                "--.........--",
+                "---.......---",
+                "------.------",
+                "-------------",
+            ]);
+        }
+
+        #[test]
+        fn walls_are_lit_but_not_seen_through() {
+            let rows = [
+                "#########",
+                "#.......#",
+                "#.#####.#",
+                "#.#...#.#",
+                "#.#.@.#.#",
+                "#.#...#.#",
+                "#.##.##.#",
+                "#.......#",
+                "#########",
+            ];
+            check(&rows, 6, FovAlgorithm::Shadowcasting, &[
+                "---------",
+                "---------",
+                "--#####--",
+                "--#...#--",
+                "--#.@.#--",
+                "--#...#--",
+                "--##.##--",
+                "----.----",
+                "---###---",
+            ]);
+            check(&rows, 6, FovAlgorithm::Permissive, &[
+                "---------",
+                "---------",
+                "--#####--",
+                "--#...#--",
+                "--#.@.#--",
+                "--#...#--",
+                "--##.##--",
+                "---...---",
+                "---###---",
+            ]);
+        }
+
+        #[test]
+        fn permissive_sees_into_side_passages() {
+            let rows = [
+                "#######",
+                "@......",
+                "###.###",
+                "###.###",
+            ];
+            check(&rows, 6, FovAlgorithm::Shadowcasting, &[
+                "#######",
+                "@......",
+                "###-###",
+                "-------",
+            ]);
+            check(&rows, 6, FovAlgorithm::Permissive, &[
+                "#######",
+                "@......",
+                "###.###",
+                "-------",
+            ]);
+        }
+
+        #[test]
+        fn sight_wraps_around_the_edges_of_the_map() {
+            // the wall on the far right is right next to the origin on the far left
+            let rows = [
+                "......#",
+                "......#",
+                "@.....#",
+                "......#",
+                "......#",
+            ];
+            check(&rows, 2, FovAlgorithm::Shadowcasting, &[
+                ".------",
+                "..----#",
+                "@..---#",
+                "..----#",
+                ".------",
+            ]);
+        }
+
+        #[test]
+        fn sight_reaches_past_the_copies_of_a_small_map() {
+            // the map is four tiles wide, so its three copies end four tiles left and seven
+            // tiles right of the origin, well within sight
+            let rows = [
+                "####",
+                "@...",
+                "####",
+            ];
+            let (map, (x, y)) = map_from(&rows);
+            let visible = compute_fov(&map, x, y, 10, true, FovAlgorithm::Shadowcasting);
+            assert!(visible.contains(&(x - 10, y)));
+            assert!(visible.contains(&(x + 10, y)));
+            assert!(!visible.contains(&(x - 11, y)));
+            assert!(!visible.contains(&(x, y + 2)));
+            assert_eq!(render(&rows, &visible), [
+                "####",
+                "@...",
+                "####",
+            ]);
+        }
+    }
+}
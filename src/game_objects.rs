@@ -1,8 +1,22 @@
 pub mod game_objects {
-    use crate::settings::settings::{COLOR_PLAINS, COLOR_MOUNTAIN, COLOR_HILL, COLOR_FOREST, COLOR_SEA, COLOR_FARM};
-    use tcod::{Color, Console, BackgroundFlag};
-    use tcod::console::{Root, Offscreen};
-    use tcod::map::{ Map as FovMap};
+    use crate::settings::settings::{COLOR_PLAINS, COLOR_MOUNTAIN, COLOR_HILL, COLOR_FOREST, COLOR_SEA, COLOR_FARM, COLOR_ROAD, COLOR_CHANNEL, ROAD_TIME_PERCENT, HILL_ELEVATION_BONUS, MOUNTAIN_ELEVATION_BONUS, DAYS_PER_YEAR, EXHAUSTED_REST, ADULT_AGE, HOUSE_CAPACITY, CARRY_CAPACITY};
+    use crate::minimap::minimap::Minimap;
+    use crate::camera::camera::Camera;
+    use crate::visibility::visibility::Visibility;
+    use crate::calendar::calendar::{Calendar, Season};
+    use crate::weather::weather::Weather;
+    use crate::renderer::renderer::{Color, Layer, Renderer};
+    use crate::fov::fov::FovAlgorithm;
+    use crate::recipes::recipes::RecipeBook;
+    use crate::settlements::settlements::Settlements;
+    use crate::wildlife::wildlife::Wildlife;
+    use crate::raiders::raiders::Raids;
+    use crate::events::events::Events;
+    use crate::research::research::{Improvement, Research};
+    use crate::messages::messages::Messages;
+    use crate::options::options::Difficulty;
+    use crate::resources::resources::{Inventory, ResourceId, ResourceRegistry};
+    use serde::Deserialize;
     use legion::*;
 
     #[derive(Clone, Copy, Debug, PartialEq)]
@@ -15,6 +29,13 @@ pub mod game_objects {
         pub fn new(x: i32, y: i32) -> Position {
             return Position { x, y };
         }
+
+        /// How many steps apart two positions are, counting diagonal steps as one. It does
+        /// not wrap around the edges of the map, as villagers and animals do not walk across
+        /// them, so it is how far they have to go.
+        pub fn distance(self, other: Position) -> i32 {
+            return (self.x - other.x).abs().max((self.y - other.y).abs());
+        }
     }
 
     #[derive(Clone, Copy, Debug, PartialEq)]
@@ -27,20 +48,26 @@ pub mod game_objects {
         pub fn new(char: char, color: Color) -> Drawable {
             return Drawable { char, color };
         }
-        pub fn draw(&self, canvas: &mut dyn Console, x: i32, y: i32) {
-            canvas.set_default_foreground(self.color);
-            canvas.put_char(x, y, self.char, BackgroundFlag::None);
+        pub fn draw(&self, renderer: &mut dyn Renderer, layer: Layer, x: i32, y: i32) {
+            renderer.put_glyph(layer, x, y, self.char, self.color);
         }
     }
 
     #[derive(Clone, Copy, Debug, PartialEq)]
     pub(crate) struct Vision {
-        pub grants_vision: bool
+        pub grants_vision: bool,
+        pub radius: i32,
+        pub algorithm: Option<FovAlgorithm>
     }
 
     impl Vision {
-        pub fn new(active: bool) -> Vision {
-            return Vision { grants_vision: active }
+        pub fn new(radius: i32) -> Vision {
+            return Vision { grants_vision: true, radius, algorithm: None }
+        }
+
+        pub fn with_algorithm(mut self, algorithm: FovAlgorithm) -> Vision {
+            self.algorithm = Some(algorithm);
+            return self;
         }
     }
 
@@ -55,10 +82,11 @@ pub mod game_objects {
         }
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[derive(Clone, Debug, PartialEq)]
     pub(crate) struct House {
         pub time_since_last_spawn: u128,
-        pub population: i32
+        pub residents: Vec<Entity>,
+        pub capacity: usize
     }
 
     impl House {
@@ -66,177 +94,655 @@ pub mod game_objects {
 
         pub fn new() -> House {
             return House {
-                population: 0,
+                residents: Vec::new(),
+                capacity: HOUSE_CAPACITY,
                 time_since_last_spawn: 0
             }
         }
+
+        pub fn population(&self) -> usize {
+            return self.residents.len();
+        }
+
+        pub fn is_full(&self) -> bool {
+            return self.residents.len() >= self.capacity;
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Watchtower;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Scout;
+
+    /// The settlement a villager or building belongs to. The player's colony is `PLAYER`,
+    /// rival settlements are numbered from one.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub(crate) struct Faction(pub usize);
+
+    impl Faction {
+        pub const PLAYER: Faction = Faction(0);
+    }
+
+    /// Marks anything villagers should run away from.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Danger;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Skills {
+        pub farming: i32,
+        pub gathering: i32
+    }
+
+    impl Skills {
+        pub const MAX_LEVEL: i32 = 100;
+
+        pub fn new() -> Skills {
+            return Skills { farming: 0, gathering: 0 }
+        }
+
+        /// Percentage of the base yield a worker of the given skill level brings in.
+        pub fn yield_percent(level: i32) -> i32 {
+            return 50 + level;
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Job {
+        Gatherer(ResourceId),
+        /// Works at the production building with the given entity.
+        Crafter(Entity),
+        /// Defends the colony from the barracks with the given entity.
+        Militia(Entity),
+        /// Studies at the library with the given entity.
+        Scholar(Entity),
+        /// Works on the earthwork with the given entity.
+        Labourer(Entity),
+    }
+
+    impl Job {
+        /// The building the job is done at, if it is done at one.
+        pub fn workplace(&self) -> Option<Entity> {
+            return match self {
+                Job::Gatherer(_) => None,
+                Job::Crafter(building) | Job::Militia(building) | Job::Scholar(building) | Job::Labourer(building) => Some(*building),
+            };
+        }
     }
 
     #[derive(Clone, Debug, PartialEq)]
     pub(crate) struct Person {
-        name: String,
-        pub home: Position,
-        pub time_since_last_movement: u128,
-        pub time_since_last_harvest: u128,
+        pub name: String,
+        pub home: Option<Entity>,
+        pub age: i32,
+        pub hunger: i32,
+        pub rest: i32,
+        pub social: i32,
+        pub carrying: Inventory,
+        pub job: Option<Job>,
+        pub skills: Skills,
+        pub time_since_last_action: u128,
     }
 
     impl Person {
         pub const TIME_BETWEEN_ACTIONS: u128 = 1000;
+        pub const MAX_NEED: i32 = 100;
 
-        pub fn new(x: i32, y: i32) -> Person {
+        pub fn new(home: Option<Entity>, name: String, age: i32) -> Person {
             return Person {
-                name: String::from("Bob"),
-                home: Position::new(x, y),
-                time_since_last_movement: 0,
-                time_since_last_harvest: 0,
+                name,
+                home,
+                age,
+                hunger: 0,
+                rest: Person::MAX_NEED,
+                social: 0,
+                carrying: Inventory::new(),
+                job: None,
+                skills: Skills::new(),
+                time_since_last_action: 0,
             }
         }
+
+        pub fn age_in_years(&self) -> i32 {
+            return self.age / DAYS_PER_YEAR;
+        }
+
+        pub fn is_adult(&self) -> bool {
+            return self.age_in_years() >= ADULT_AGE;
+        }
+
+        pub fn is_exhausted(&self) -> bool {
+            return self.rest <= EXHAUSTED_REST;
+        }
+
+        /// Adds as much of the amount to the villager's load as they have room for, returning
+        /// how much they picked up.
+        pub fn pick_up(&mut self, id: ResourceId, amount: i32) -> i32 {
+            let picked_up = amount.min(CARRY_CAPACITY - self.carrying.total()).max(0);
+            self.carrying.add(id, picked_up);
+            return picked_up;
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+    pub(crate) enum Terrain {
+        Meadow,
+        Forest,
+        Hill,
+        Mountain,
+        Water
     }
 
     #[derive(Clone, Copy, Debug, PartialEq)]
     pub struct Tile {
+        pub(crate) terrain: Terrain,
         blocked: bool,
         block_sight: bool,
         pub(crate) explored: bool,
         buildable: bool,
+        built_on: bool,
+        flooded: bool,
+        road: bool,
+        channel: bool,
         pub(crate) color: Color,
         pub fertility: i32,
+        pub elevation: i32,
+    }
+
+    /// Running totals shown on the game-over screen.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct RunStats {
+        pub peak_population: i32,
+        pub buildings_built: i32,
     }
 
-    pub struct Tcod {
-        pub root: Root,
-        pub con: Offscreen,
-        pub panel: Offscreen,
-        pub fov: FovMap
+    /// A listener's place on the map, which keeps the tiles changed since each listener last
+    /// heard from it.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) struct ListenerId(usize);
+
+    /// Anything that mirrors which tiles can be walked or seen through, like the visibility
+    /// layer or a pathfinding cache. The map tells its listeners about every tile that changes.
+    pub(crate) trait TileListener {
+        /// The id the listener was given when it registered with the map.
+        fn listener_id(&self) -> ListenerId;
+        fn tile_changed(&mut self, x: i32, y: i32, transparent: bool, walkable: bool);
     }
 
     pub struct GameMap {
-        tiles: Vec<Vec<Tile>>
+        tiles: Vec<Vec<Tile>>,
+        width: i32,
+        height: i32,
+        changed_tiles: Vec<(i32, i32)>,
+        /// The tiles each registered listener has yet to hear about.
+        reshaped_tiles: Vec<Vec<(i32, i32)>>,
     }
 
     impl GameMap {
+        /// Takes the tiles of all nine copies of the wrapped map.
         pub fn new(tiles: Vec<Vec<Tile>>) -> GameMap {
-            return GameMap { tiles };
+            let width = tiles.len() as i32 / 3;
+            let height = tiles[0].len() as i32 / 3;
+            return GameMap { tiles, width, height, changed_tiles: Vec::new(), reshaped_tiles: Vec::new() };
+        }
+
+        /// Width of a single copy of the map.
+        pub fn width(&self) -> i32 {
+            return self.width;
+        }
+
+        /// Height of a single copy of the map.
+        pub fn height(&self) -> i32 {
+            return self.height;
+        }
+
+        /// Registers a new listener, which will hear about every tile changed from now on.
+        pub fn register_listener(&mut self) -> ListenerId {
+            self.reshaped_tiles.push(Vec::new());
+            return ListenerId(self.reshaped_tiles.len() - 1);
+        }
+
+        /// Tells the listener about every tile whose walkability or transparency changed since
+        /// it was last notified. Each listener hears about every change, whatever order they
+        /// are notified in.
+        pub fn notify(&mut self, listener: &mut dyn TileListener) {
+            let ListenerId(index) = listener.listener_id();
+            for (x, y) in std::mem::take(&mut self.reshaped_tiles[index]) {
+                listener.tile_changed(x, y, !self.is_tile_blocking_vision(x as usize, y as usize), !self.is_tile_blocked(x, y));
+            }
+        }
+
+        /// Returns the tiles whose appearance changed since the last call.
+        pub fn take_changed_tiles(&mut self) -> Vec<(i32, i32)> {
+            return std::mem::take(&mut self.changed_tiles);
         }
 
         pub fn is_tile_blocked(&self, x: i32, y: i32) -> bool {
-            return self.tiles[x as usize][y as usize].blocked;
+            return self.tiles[x as usize][y as usize].is_blocked();
         }
 
         pub fn is_buildable(&self, x: i32, y: i32) -> bool {
-            return self.tiles[x as usize][y as usize].buildable;
+            return self.tiles[x as usize][y as usize].is_buildable();
+        }
+
+        /// Watchtowers can also be raised on hills to make use of their height.
+        pub fn is_watchtower_buildable(&self, x: i32, y: i32) -> bool {
+            let tile = &self.tiles[x as usize][y as usize];
+            return tile.is_buildable() || (tile.elevation == HILL_ELEVATION_BONUS && !tile.built_on);
+        }
+
+        /// The positions of a tile in each of the nine copies of the wrapped map.
+        fn copies(&self, x: i32, y: i32) -> Vec<(usize, usize)> {
+            let (x, y) = (x.rem_euclid(self.width), y.rem_euclid(self.height));
+            let mut copies = Vec::with_capacity(9);
+            for i in 0..3 {
+                for j in 0..3 {
+                    copies.push(((x + self.width * i) as usize, (y + self.height * j) as usize));
+                }
+            }
+            return copies;
+        }
+
+        pub fn elevation(&self, x: i32, y: i32) -> i32 {
+            return self.tiles[x as usize][y as usize].elevation;
         }
 
 
         pub fn make_tile_built_on(&mut self, x: i32, y: i32) {
-            self.tiles[x as usize][y as usize].blocked = true;
-            self.tiles[x as usize][y as usize].buildable = false;
+            self.reshape(x, y, |tile| tile.built_on = true);
+        }
+
+        pub fn remove_building(&mut self, x: i32, y: i32) {
+            self.reshape(x, y, |tile| tile.built_on = false);
+        }
+
+        pub fn terrain(&self, x: i32, y: i32) -> Terrain {
+            return self.tiles[x.rem_euclid(self.width * 3) as usize][y.rem_euclid(self.height * 3) as usize].terrain;
         }
 
+        /// Changes a tile in every copy of the wrapped map. Every change to what can be walked
+        /// or seen through goes through here so listeners hear about it.
+        fn reshape(&mut self, x: i32, y: i32, change: impl Fn(&mut Tile)) {
+            for (x, y) in self.copies(x, y) {
+                change(&mut self.tiles[x][y]);
+                self.changed_tiles.push((x as i32, y as i32));
+                for pending in self.reshaped_tiles.iter_mut() {
+                    pending.push((x as i32, y as i32));
+                }
+            }
+        }
+
+        /// Turns a forest into open meadow.
+        pub fn clear_forest(&mut self, x: i32, y: i32) {
+            let meadow = Tile::meadow();
+            self.reshape(x, y, |tile| {
+                tile.terrain = meadow.terrain;
+                tile.color = meadow.color;
+                tile.fertility = meadow.fertility;
+            });
+        }
+
+        /// Lays a road. Nothing grows on a road.
+        pub fn build_road(&mut self, x: i32, y: i32) {
+            self.reshape(x, y, |tile| {
+                tile.road = true;
+                tile.fertility = 0;
+            });
+        }
+
+        /// Digs an irrigation channel, which more channels can be dug on from.
+        pub fn dig_channel(&mut self, x: i32, y: i32) {
+            self.reshape(x, y, |tile| tile.channel = true);
+        }
+
+        /// Digs through a hill, leaving a passage that can be walked and seen through.
+        pub fn tunnel(&mut self, x: i32, y: i32) {
+            self.reshape(x, y, |tile| {
+                tile.blocked = false;
+                tile.block_sight = false;
+            });
+        }
+
+        /// Whether a road has been laid on the tile.
+        pub fn is_road(&self, x: i32, y: i32) -> bool {
+            return self.tiles[x as usize][y as usize].road;
+        }
+
+        /// Whether an irrigation channel has been dug through the tile.
+        pub fn is_channel(&self, x: i32, y: i32) -> bool {
+            return self.tiles[x as usize][y as usize].channel;
+        }
+
+        /// How long it takes to step off a tile, roads being quicker to walk than open ground.
+        pub fn move_time(&self, x: i32, y: i32, time: u128) -> u128 {
+            return if self.is_road(x, y) { time * ROAD_TIME_PERCENT / 100 } else { time };
+        }
+
+        /// Changes how fertile a tile is in every copy of the wrapped map.
+        pub fn change_fertility(&mut self, x: i32, y: i32, change: i32) {
+            for (x, y) in self.copies(x, y) {
+                let tile = &mut self.tiles[x][y];
+                tile.fertility = (tile.fertility + change).max(0);
+            }
+        }
+
+        pub fn is_water(&self, x: i32, y: i32) -> bool {
+            return self.terrain(x, y) == Terrain::Water;
+        }
+
+        /// Floods or drains a tile in every copy of the wrapped map.
+        pub fn set_flooded(&mut self, flooded: bool, x: i32, y: i32) {
+            self.reshape(x, y, |tile| tile.flooded = flooded);
+        }
+
+        /// Buildings block sight as well as movement.
         pub fn is_tile_blocking_vision(&self, x: usize, y: usize) -> bool {
-            return self.tiles[x][y].block_sight;
+            let tile = &self.tiles[x][y];
+            return tile.block_sight || tile.built_on;
+        }
+
+        /// Whether a tile blocks sight, wrapping around the edges of the map.
+        pub fn blocks_sight(&self, x: i32, y: i32) -> bool {
+            return self.is_tile_blocking_vision(x.rem_euclid(self.width * 3) as usize, y.rem_euclid(self.height * 3) as usize);
         }
 
         pub fn get_tile(&self, x: usize, y: usize) -> Tile {
             return self.tiles[x][y];
         }
 
+        pub fn is_explored(&self, x: usize, y: usize) -> bool {
+            return self.tiles[x][y].explored;
+        }
+
         pub fn set_tile_explored(&mut self, explored: bool, x: usize, y: usize) {
             self.tiles[x][y].explored = explored;
         }
 
-        pub fn harvest(&mut self, x: i32, y: i32) -> i32 {
-            return self.tiles[x as usize][y as usize].harvest();
+        /// Marks the tile as explored in every copy of the wrapped map.
+        pub fn explore(&mut self, x: i32, y: i32) {
+            for (x, y) in self.copies(x, y) {
+                if !self.is_explored(x, y) {
+                    self.set_tile_explored(true, x, y);
+                    self.changed_tiles.push((x as i32, y as i32));
+                }
+            }
+        }
+
+        pub fn harvest(&mut self, x: i32, y: i32, season: Season) -> i32 {
+            let tile = &mut self.tiles[x as usize][y as usize];
+            let color = tile.color;
+            let harvest = tile.harvest(season);
+            if tile.color != color {
+                self.changed_tiles.push((x, y));
+            }
+            return harvest;
         }
     }
 
     pub struct Game {
         pub(crate) map: GameMap,
-        pub(crate) camera_height: i32,
-        pub(crate) camera_width: i32,
+        pub(crate) camera: Camera,
+        pub(crate) minimap: Minimap,
+        pub(crate) visibility: Visibility,
+        pub(crate) calendar: Calendar,
+        pub(crate) weather: Weather,
+        pub(crate) settlements: Settlements,
+        pub(crate) raids: Raids,
+        pub(crate) events: Events,
+        pub(crate) wildlife: Wildlife,
+        pub(crate) messages: Messages,
+        pub(crate) inspected: Option<Entity>,
         pub(crate) population: i32,
-        pub(crate) wood: i32,
-        pub(crate) food: i32,
+        pub(crate) stats: RunStats,
+        pub(crate) difficulty: Difficulty,
+        pub(crate) resources: ResourceRegistry,
+        pub(crate) recipes: RecipeBook,
+        pub(crate) research: Research,
+        pub(crate) stock: Inventory,
+        pub(crate) selected_workshop: usize,
         pub(crate) world: World
     }
 
     impl Tile {
         pub fn is_blocked(&self,) -> bool {
-            return self.blocked;
+            return self.blocked || self.built_on || self.flooded;
+        }
+
+        pub fn is_buildable(&self) -> bool {
+            return self.buildable && !self.built_on && !self.flooded;
+        }
+
+        pub fn is_built_on(&self) -> bool {
+            return self.built_on;
+        }
+
+        pub fn display_color(&self) -> Color {
+            return if self.flooded {
+                COLOR_SEA
+            } else if self.channel {
+                COLOR_CHANNEL
+            } else if self.road {
+                COLOR_ROAD
+            } else {
+                self.color
+            };
         }
 
-        pub fn harvest(&mut self) -> i32 {
-            if self.color == COLOR_PLAINS {
+        /// Nothing grows in winter, so fields are only sown when there is a crop to take.
+        pub fn harvest(&mut self, season: Season) -> i32 {
+            if self.flooded {
+                return 0;
+            }
+            let harvest = self.fertility * season.fertility_percent() / 100;
+            if self.color == COLOR_PLAINS && harvest > 0 {
                 self.color = COLOR_FARM;
             }
-            return self.fertility;
+            return harvest;
         }
 
         pub fn meadow() -> Self {
             Tile {
+                terrain: Terrain::Meadow,
                 blocked: false,
                 block_sight: false,
                 explored: false,
                 buildable: true,
+                built_on: false,
+                flooded: false,
+                road: false,
+                channel: false,
                 color: COLOR_PLAINS,
                 fertility: 3,
+                elevation: 0,
             }
         }
 
         pub fn mountain() -> Self {
             Tile {
+                terrain: Terrain::Mountain,
                 blocked: true,
                 block_sight: true,
                 explored: false,
                 buildable: false,
+                built_on: false,
+                flooded: false,
+                road: false,
+                channel: false,
                 color: COLOR_MOUNTAIN,
                 fertility: 0,
+                elevation: MOUNTAIN_ELEVATION_BONUS,
             }
         }
 
         pub fn hill() -> Self {
             Tile {
+                terrain: Terrain::Hill,
                 blocked: true,
                 block_sight: true,
                 explored: false,
                 buildable: false,
+                built_on: false,
+                flooded: false,
+                road: false,
+                channel: false,
                 color: COLOR_HILL,
                 fertility: 1,
+                elevation: HILL_ELEVATION_BONUS,
             }
         }
 
         pub fn forest() -> Self {
             Tile {
+                terrain: Terrain::Forest,
                 blocked: false,
                 block_sight: false,
                 explored: false,
                 buildable: true,
+                built_on: false,
+                flooded: false,
+                road: false,
+                channel: false,
                 color: COLOR_FOREST,
                 fertility: 1,
+                elevation: 0,
             }
         }
 
         pub fn water() -> Self {
             Tile {
+                terrain: Terrain::Water,
                 blocked: true,
                 block_sight: false,
                 explored: false,
                 buildable: false,
+                built_on: false,
+                flooded: false,
+                road: false,
+                channel: false,
                 color: COLOR_SEA,
                 fertility: 3,
+                elevation: 0,
             }
         }
     }
 
-    #[derive(PartialEq, Debug)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
     pub(crate) enum Action {
         MoveUp,
         MoveDown,
         MoveLeft,
         MoveRight,
         Build,
+        BuildWatchtower,
+        BuildStockpile,
+        BuildWorkshop,
+        BuildBarracks,
+        BuildGuardTower,
+        BuildLibrary,
+        OrderEarthwork(Improvement),
+        NextWorkshop,
+        NextRecipe,
+        SendGift,
+        ToggleMinimap,
+        MoveCamera(i32, i32),
+        ScrollCamera(i32, i32),
+        Inspect(i32, i32),
+        FollowPlayer,
+        FollowVillager,
+        JumpToSettlement,
+        OpenResearch,
         FullScreen,
-        Quit,
+        Pause,
+    }
+
+
+    /// Removes a villager from the world and from the residents of their house.
+    pub(crate) fn remove_person(world: &mut World, person: Entity) {
+        let home = match world.entry(person) {
+            Some(entry) => entry.get_component::<Person>().ok().and_then(|person| person.home),
+            None => return,
+        };
+        if let Some(mut entry) = home.and_then(|home| world.entry(home)) {
+            if let Ok(house) = entry.get_component_mut::<House>() {
+                house.residents.retain(|resident| *resident != person);
+            }
+        }
+        world.remove(person);
+    }
+
+    /// Tears down a building, freeing its tile and leaving any residents homeless.
+    pub(crate) fn destroy_building(world: &mut World, map: &mut GameMap, building: Entity) {
+        let (position, residents) = match world.entry(building) {
+            Some(entry) => {
+                let position = entry.get_component::<Position>().ok().copied();
+                let residents = entry.get_component::<House>().map(|house| house.residents.clone()).unwrap_or_default();
+                (position, residents)
+            }
+            None => return,
+        };
+        for resident in residents {
+            if let Some(mut entry) = world.entry(resident) {
+                if let Ok(person) = entry.get_component_mut::<Person>() {
+                    person.home = None;
+                }
+            }
+        }
+        world.remove(building);
+        if let Some(position) = position {
+            map.remove_building(position.x, position.y);
+        }
     }
 
-}
\ No newline at end of file
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Records the tiles it hears about.
+        struct Recorder {
+            id: ListenerId,
+            heard: Vec<(i32, i32, bool, bool)>,
+        }
+
+        impl TileListener for Recorder {
+            fn listener_id(&self) -> ListenerId {
+                return self.id;
+            }
+
+            fn tile_changed(&mut self, x: i32, y: i32, transparent: bool, walkable: bool) {
+                self.heard.push((x, y, transparent, walkable));
+            }
+        }
+
+        #[test]
+        fn loads_stop_at_the_carry_capacity() {
+            let mut person = Person::new(None, String::from("Ada"), 0);
+            assert_eq!(person.pick_up(ResourceId(0), CARRY_CAPACITY - 5), CARRY_CAPACITY - 5);
+            assert_eq!(person.pick_up(ResourceId(1), 10), 5);
+            assert_eq!(person.pick_up(ResourceId(1), 10), 0);
+            assert_eq!(person.carrying.total(), CARRY_CAPACITY);
+        }
+
+        #[test]
+        fn every_listener_hears_every_change() {
+            let mut map = GameMap::new(vec![vec![Tile::meadow(); 6]; 6]);
+            let mut first = Recorder { id: map.register_listener(), heard: Vec::new() };
+            let mut second = Recorder { id: map.register_listener(), heard: Vec::new() };
+            map.make_tile_built_on(2, 3);
+
+            map.notify(&mut first);
+            map.notify(&mut second);
+            // the building is raised in all nine copies of the wrapped map and blocks sight
+            let mut heard = first.heard.clone();
+            heard.sort();
+            assert_eq!(heard, vec![
+                (0, 1, false, false), (0, 3, false, false), (0, 5, false, false),
+                (2, 1, false, false), (2, 3, false, false), (2, 5, false, false),
+                (4, 1, false, false), (4, 3, false, false), (4, 5, false, false),
+            ]);
+            assert_eq!(second.heard, first.heard);
+
+            map.notify(&mut first);
+            assert_eq!(first.heard.len(), 9);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+pub mod input {
+    /// The keys the game responds to. Letters, digits and other printable keys come as the
+    /// character they type.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) enum Key {
+        Up,
+        Down,
+        Left,
+        Right,
+        Enter,
+        Escape,
+        Backspace,
+        Space,
+        Char(char),
+    }
+
+    /// Where the mouse is, in cells on the screen.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) struct Mouse {
+        pub x: i32,
+        pub y: i32,
+        /// Whether the left button was pressed since the last event.
+        pub left_pressed: bool,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) enum Event {
+        Key { key: Key, alt: bool },
+        Mouse(Mouse),
+    }
+
+    /// Everything the game needs from the player's side of a display: their key presses and
+    /// mouse movements, and the clock the simulation is stepped by.
+    pub(crate) trait Input {
+        /// The next event waiting, if there is one. Never waits for the player.
+        fn poll(&mut self) -> Option<Event>;
+        /// Milliseconds since the program started.
+        fn elapsed_millis(&self) -> u128;
+    }
+}
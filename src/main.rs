@@ -1,28 +1,105 @@
+// without the tcod feature only the tests have a frontend to run the game through
+#![cfg_attr(not(feature = "tcod"), allow(dead_code))]
 use noise::{NoiseFn, Perlin};
-use tcod::colors::*;
-use tcod::console::*;
-use tcod::map::{ Map as FovMap};
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 mod settings;
+mod renderer;
+use crate::renderer::renderer::{Color, Layer, Renderer, BLACK, WHITE};
+mod input;
+use crate::input::input::{Event, Input, Key, Mouse};
+#[cfg(feature = "tcod")]
+mod tcod_renderer;
+#[cfg(test)]
+mod memory_renderer;
+#[cfg(test)]
+mod test_maps;
+mod fov;
 use crate::settings::settings::*;
 mod game_objects;
 use crate::game_objects::game_objects::*;
+mod minimap;
+use crate::minimap::minimap::Minimap;
+mod camera;
+use crate::camera::camera::Camera;
+mod render_cache;
+use crate::render_cache::render_cache::RenderCache;
+mod visibility;
+use crate::visibility::visibility::{Visibility, VisionSource};
+mod calendar;
+use crate::calendar::calendar::Calendar;
+mod weather;
+use crate::weather::weather::Weather;
+mod names;
+use crate::names::names::generate_name;
+mod ai;
+use crate::ai::ai::{Intent, ai_system};
+mod stockpile;
+use crate::stockpile::stockpile::{Stockpile, load_stockpiles, nearest, store_goods, store_stockpiles, take_resources, total_stock};
+mod resources;
+use crate::resources::resources::{GatherMethod, Inventory, ResourceId, ResourceRegistry};
+mod messages;
+use crate::messages::messages::Messages;
+mod combat;
+use crate::combat::combat::{Attack, Defense, arm, combat_system, healing_system};
+mod wildlife;
+use crate::wildlife::wildlife::{Wildlife, animal_system};
+mod settlements;
+use crate::settlements::settlements::{Settlements, caravan_system, send_gift, settlements_system, spawn_rivals};
+mod recipes;
+use crate::recipes::recipes::RecipeBook;
+mod workshop;
+use crate::workshop::workshop::Workshop;
+mod raiders;
+use crate::raiders::raiders::{Barracks, GuardTower, Raids, describe_raids, guard_tower_system, raid_system, raider_system};
+mod events;
+use crate::events::events::{Events, can_afford, event_system};
+mod research;
+use crate::research::research::{Building, Improvement, Library, Research, Unlock, staff_libraries};
+mod earthworks;
+use crate::earthworks::earthworks::{order_earthwork, staff_earthworks};
+mod staffing;
+use crate::staffing::staffing::{jobs, staff};
+mod options;
+use crate::options::options::{NewGame, Preferences};
+mod states;
+use crate::states::states::{Outcome, Session};
+use std::collections::{HashMap, HashSet};
 use legion::{World, IntoQuery, Entity};
-use tcod::input::KEY_PRESSED;
-use crate::game_objects::game_objects::Action::{Quit, MoveUp, MoveDown, MoveLeft, MoveRight, Build, FullScreen};
-use tcod::system::get_elapsed_time;
-use rand::prelude::ThreadRng;
+use crate::game_objects::game_objects::Action::*;
+use rand::rngs::StdRng;
 
-fn make_map() -> GameMap {
-    let mut tiles = vec![vec![Tile::meadow(); (MAP_HEIGHT*3) as usize]; (MAP_WIDTH*3) as usize];
+/// The frontend: the renderer the game is drawn through, where the player's input and the
+/// time come from, and the mouse as of the last mouse event, if there has been one.
+pub(crate) struct Frontend {
+    pub renderer: Box<dyn Renderer>,
+    pub input: Box<dyn Input>,
+    pub mouse: Option<Mouse>,
+    pub render_cache: RenderCache
+}
+
+impl Frontend {
+    /// The next input event, if there is one, keeping track of the mouse as it moves.
+    pub fn poll(&mut self) -> Option<Event> {
+        let event = self.input.poll();
+        if let Some(Event::Mouse(mouse)) = event {
+            self.mouse = Some(mouse);
+        }
+        return event;
+    }
+}
+
+fn make_map(options: &NewGame) -> GameMap {
+    let (map_width, map_height) = options.map_size.dimensions();
+    let seed = options.noise_seed();
+    let mut tiles = vec![vec![Tile::meadow(); (map_height*3) as usize]; (map_width*3) as usize];
     let perlin = Perlin::new();
-    for y in 0..MAP_HEIGHT {
-        for x in 0..MAP_WIDTH {
+    for y in 0..map_height {
+        for x in 0..map_width {
             let mut height = 0.0;
             let fertility = perlin.get([x as f64 / 10f64, y as f64 / 10f64, 1.999_282_82]);
             let mountain_modifier = perlin.get([x as f64 / 10f64, y as f64 / 10f64, 2.5]);
-            height += perlin.get([x as f64 / 10f64, y as f64 / 10f64, GAME_SEED]);
-            height += perlin.get([x as f64, y as f64, GAME_SEED + 1.0]) / 7.5;
+            height += perlin.get([x as f64 / 10f64, y as f64 / 10f64, seed]);
+            height += perlin.get([x as f64, y as f64, seed + 1.0]) / 7.5;
 
             if height >= -0.1 {
                 height += mountain_modifier.abs()
@@ -31,13 +108,13 @@ fn make_map() -> GameMap {
             for i in 0..3 {
                 for j in 0..3 {
                     if height >= 1.1 {
-                        tiles[(x + (MAP_WIDTH*i))  as usize][(y + (MAP_HEIGHT*j)) as usize] = Tile::mountain()
+                        tiles[(x + (map_width*i))  as usize][(y + (map_height*j)) as usize] = Tile::mountain()
                     } else if height >= 0.50 {
-                        tiles[(x + (MAP_WIDTH*i)) as usize][(y + (MAP_HEIGHT*j)) as usize] = Tile::hill()
+                        tiles[(x + (map_width*i)) as usize][(y + (map_height*j)) as usize] = Tile::hill()
                     } else if height < -0.175 {
-                        tiles[(x + (MAP_WIDTH*i)) as usize][(y + (MAP_HEIGHT*j)) as usize] = Tile::water()
+                        tiles[(x + (map_width*i)) as usize][(y + (map_height*j)) as usize] = Tile::water()
                     } else if fertility >= 0.25 {
-                        tiles[(x + (MAP_WIDTH*i)) as usize][(y + (MAP_HEIGHT*j)) as usize] = Tile::forest()
+                        tiles[(x + (map_width*i)) as usize][(y + (map_height*j)) as usize] = Tile::forest()
                     }
                 }
             }
@@ -48,136 +125,319 @@ fn make_map() -> GameMap {
     GameMap::new(tiles)
 }
 
-fn render_all(tcod: &mut Tcod, game: &mut Game, fov_recompute: bool, player: Entity) {
+/// Recomputes the shared visibility layer when a vision source moved or the map changed,
+/// marking every newly lit tile as explored.
+fn update_visibility(frontend: &mut Frontend, game: &mut Game) {
+    let mut query = <(Entity, &Vision, &Position)>::query();
+    let sources: HashMap<Entity, VisionSource> = query.iter(&game.world)
+        .filter(|(_, vision, _)| vision.grants_vision)
+        .map(|(entity, vision, position)| {
+            let source = VisionSource {
+                position: *position,
+                radius: game.calendar.vision_radius(vision.radius + game.map.elevation(position.x, position.y)),
+                algorithm: vision.algorithm.unwrap_or(FOV_ALGO),
+            };
+            (*entity, source)
+        })
+        .collect();
 
-    if fov_recompute {
-        let mut query = <(&Vision, &mut Position)>::query();
-        let things_with_vision = query.iter_mut(&mut game.world);
+    game.map.notify(&mut game.visibility);
+    if !game.visibility.is_stale(&sources) {
+        return;
+    }
+
+    game.visibility.recompute(&game.map, sources);
+    for &(x, y) in game.visibility.visible_cells() {
+        game.map.explore(x, y);
+    }
+    frontend.render_cache.set_visible_cells(game.visibility.visible_cells().to_vec());
+}
+
+fn render_all(frontend: &mut Frontend, game: &mut Game) {
 
-        for (vision, position) in things_with_vision {
-            if vision.grants_vision {
-                tcod.fov.compute_fov(
-                position.x,
-                position.y,
-                TORCH_RADIUS,
-                FOV_LIGHT_WALLS,
-                FOV_ALGO
-                );
+    for (x, y) in game.map.take_changed_tiles() {
+        frontend.render_cache.mark_dirty(x, y);
+        game.minimap.tile_changed(x, y);
+    }
+
+    let mut query = <(Entity, &Drawable, &Position)>::query();
+    let positions = query.iter(&game.world)
+        .map(|(entity, _, position)| (*entity, *position))
+        .collect();
+    frontend.render_cache.track_entities(positions);
+
+    let top = game.camera.top();
+    let left = game.camera.left();
+
+    for (x, y) in frontend.render_cache.begin_frame(&game.camera) {
+        let visible = game.visibility.is_visible(x, y);
+        let tile = game.map.get_tile(x as usize, y as usize);
+        let tile_color = tile.display_color();
+        let color = if visible {
+            tile_color
+        } else if !tile.explored {
+            COLOR_UNEXPLORED
+        } else {
+            Color {
+                r: tile_color.r / 3,
+                g: tile_color.g / 3,
+                b: tile_color.b / 3,
             }
-        }
+        };
+        frontend.renderer.put_glyph(Layer::Map, x, y, ' ', WHITE);
+        frontend.renderer.set_background(Layer::Map, x, y, color);
     }
 
-    let mut query = <&Position>::query();
-    let position = query.get(&mut game.world, player).unwrap().clone();
-
-    let top = position.y - (game.camera_height / 2);
-    let bottom = position.y + (game.camera_height / 2);
-    let left = position.x - (game.camera_width / 2);
-    let right = position.x + (game.camera_width / 2);
-
-    for y in top..bottom {
-        for x in left..right {
-            for vertical_offset in -1..2 {
-                for horizontal_offset in -1..2 {
-                    let visible = tcod.fov.is_in_fov(x, y);
-                    let mut x = x + (MAP_WIDTH * horizontal_offset);
-                    x = if x < 0 { 0 } else if x >= (MAP_WIDTH*3) { (MAP_WIDTH*3) - 1 } else {x};
-                    let mut y = y + (MAP_HEIGHT * vertical_offset);
-                    y = if y < 0 { 0 } else if y >= (MAP_HEIGHT*3) { (MAP_HEIGHT*3) - 1 } else {y};
-                    if visible {
-                        game.map.set_tile_explored(true, x as usize, y as usize);
-                    }
-                }
-            };
-            let visible = tcod.fov.is_in_fov(x, y);
-            let tile = game.map.get_tile(x as usize, y as usize);
-            let color = if visible {
-                game.map.set_tile_explored(true, x as usize, y as usize);
-                tile.color
-            } else if !tile.explored {
-                Color {
-                    r: 242,
-                    g: 227,
-                    b: 211,
-                }
-            } else {
-                Color {
-                    r: tile.color.r / 3,
-                    g: tile.color.g / 3,
-                    b: tile.color.b / 3,
-                }
-            };
-            tcod.con
-                .set_char_background(x, y, color, BackgroundFlag::Set);
+    for (_, drawable, position) in query.iter(&game.world) {
+        if frontend.render_cache.is_dirty(position.x, position.y) {
+            drawable.draw(frontend.renderer.as_mut(), Layer::Map, position.x, position.y)
         }
     }
+    frontend.render_cache.end_frame();
 
-    let mut query = <(&Drawable, &Position)>::query();
-    for (drawable, position) in query.iter_mut(&mut game.world) {
-        drawable.draw(&mut tcod.con, position.x, position.y)
-    }
 
+    frontend.renderer.compose(Layer::Map, (left, top), (game.camera.width, game.camera.height), (0, 0), 1.0);
 
-    let source_x = position.x - (game.camera_width / 2);
-    let source_y = position.y - (game.camera_height / 2);
+    let darkness = game.calendar.darkness();
+    if darkness > 0.0 {
+        frontend.renderer.clear(Layer::Night, COLOR_NIGHT);
+        frontend.renderer.compose(Layer::Night, (0, 0), (game.camera.width, game.camera.height), (0, 0), darkness);
+    }
+
+    if game.minimap.visible {
+        game.minimap.refresh(&game.map);
+        game.minimap.render(frontend.renderer.as_mut(), game);
+        frontend.renderer.compose(Layer::Minimap, (0, 0), game.minimap.console_size(), (game.minimap.x, game.minimap.y), 1.0);
+    }
 
-    blit(
-        &tcod.con,
-        (source_x, source_y),
-        (game.camera_width, game.camera_height),
-        &mut tcod.root,
-        (0, 0),
-        1.0,
-        1.0,
-    );
 
+    render_inspector(frontend, game);
 
     //GUI rendering
 
-    tcod.panel.set_default_background(BLACK);
-    tcod.panel.clear();
+    frontend.renderer.clear(Layer::Panel, BLACK);
 
+    // the colony details on the left are cut short rather than run into the stock
+    let details = STOCK_X - 1;
     let population = format!("Population {}", game.population.to_string());
-    tcod.panel.print(0, 0, population);
-    let wood = format!("Wood {}", game.wood.to_string());
-    tcod.panel.print(0, 1, wood);
-    let iron = format!("Food {}", game.food.to_string());
-    tcod.panel.print(0, 2, iron);
-
-    // blit the contents of `panel` to the root console
-    blit(
-        &tcod.panel,
-        (0, 0),
-        (0, 0),
-        &mut tcod.root,
-        (0, 0),
-        1.0,
-        1.0,
-    );
-}
-
-fn handle_keys(tcod: &mut Tcod) -> Vec<Action> {
-    use tcod::input::Key;
-    use tcod::input::KeyCode::*;
+    frontend.renderer.print(Layer::Panel, 0, 0, &clip(&population, details), WHITE);
+    frontend.renderer.print(Layer::Panel, 0, 1, &clip(&game.calendar.describe(), details), WHITE);
+    frontend.renderer.print(Layer::Panel, 0, 2, &clip(&game.weather.describe(), details), WHITE);
+    if let Some(report) = &game.weather.last_report {
+        frontend.renderer.print(Layer::Panel, 0, 3, &clip(report, details), WHITE);
+    }
+    let workshop = &game.recipes.workshops[game.selected_workshop];
+    let workshop = format!("Workshop: {} ({})", workshop.name, describe_amounts(&game.resources, &workshop.cost));
+    frontend.renderer.print(Layer::Panel, 0, 4, &clip(&workshop, details), WHITE);
 
+    // the neighbours grow with every rival, so they have the row to themselves
+    frontend.renderer.print(Layer::Panel, 0, 5, &clip(&game.settlements.describe(), MESSAGES_X - 1), WHITE);
+    if let Some(report) = &game.settlements.last_report {
+        frontend.renderer.print(Layer::Panel, 0, 6, &clip(report, details), WHITE);
+    }
+    frontend.renderer.print(Layer::Panel, 0, 7, &clip(&game.research.describe(), details), WHITE);
+
+    let raids = MESSAGES_X - STOCK_X - 1;
+    frontend.renderer.print(Layer::Panel, STOCK_X, 6, &clip(&describe_raids(game), raids), WHITE);
+    if let Some(report) = &game.raids.last_report {
+        frontend.renderer.print(Layer::Panel, STOCK_X, 7, &clip(report, raids), WHITE);
+    }
+
+    // the newest messages are at the bottom of the log on the far right
+    for (row, (message, color)) in game.messages.latest().take(PANEL_HEIGHT as usize).enumerate() {
+        frontend.renderer.print(Layer::Panel, MESSAGES_X, PANEL_HEIGHT - 1 - row as i32, message, *color);
+    }
+
+    // stock is listed in columns to the right of the colony details, above the neighbours
+    for (index, id) in game.resources.ids().enumerate() {
+        let column = STOCK_X + (index as i32 / 5) * 16;
+        let row = index as i32 % 5;
+        frontend.renderer.print(Layer::Panel, column, row, &format!("{} {}", game.resources.get(id).name, game.stock.get(id)), WHITE);
+    }
+
+    let (screen_width, _) = frontend.renderer.screen_size();
+    frontend.renderer.compose(Layer::Panel, (0, 0), (screen_width, PANEL_HEIGHT), (0, 0), 1.0);
+}
+
+/// Shows the event waiting on the player over the panel, with its choices numbered. Choices
+/// the colony cannot pay for are greyed out.
+fn render_event(frontend: &mut Frontend, game: &Game) {
+    let event = match game.events.pending_event() {
+        Some(event) => event,
+        None => return,
+    };
+    frontend.renderer.print_frame(Layer::Popup, (0, 0), (EVENT_WIDTH, EVENT_HEIGHT), Some(event.title.as_str()));
+    let text_height = frontend.renderer.print_wrapped(Layer::Popup, (2, 2), (EVENT_WIDTH - 4, EVENT_HEIGHT - 4), &event.text, WHITE);
+    for (index, choice) in event.choices.iter().enumerate() {
+        let color = if can_afford(game, choice) { WHITE } else { COLOR_UNAFFORDABLE };
+        let row = 3 + text_height + index as i32;
+        frontend.renderer.print(Layer::Popup, 2, row, &format!("{}. {}", index + 1, choice.describe(&game.resources)), color);
+    }
+
+    frontend.renderer.compose(Layer::Popup, (0, 0), (EVENT_WIDTH, EVENT_HEIGHT), ((game.camera.width - EVENT_WIDTH) / 2, 0), 1.0);
+}
+
+/// Shows the details of the inspected villager or workshop in the bottom left corner of the
+/// screen.
+fn render_inspector(frontend: &mut Frontend, game: &mut Game) {
+    let entity = match game.inspected {
+        Some(entity) => entity,
+        None => return,
+    };
+    let mut person_query = <&Person>::query();
+    let mut workshop_query = <&Workshop>::query();
+    if let Ok(person) = person_query.get(&game.world, entity) {
+        render_villager(frontend, game, entity, person);
+    } else if let Ok(workshop) = workshop_query.get(&game.world, entity) {
+        render_workshop(frontend, game, workshop);
+    } else {
+        game.inspected = None;
+        return;
+    }
+
+    frontend.renderer.compose(Layer::Inspect, (0, 0), (INSPECT_WIDTH, INSPECT_HEIGHT), (0, game.camera.height - INSPECT_HEIGHT), 1.0);
+}
+
+fn render_villager(frontend: &mut Frontend, game: &Game, entity: Entity, person: &Person) {
+    frontend.renderer.print_frame(Layer::Inspect, (0, 0), (INSPECT_WIDTH, INSPECT_HEIGHT), Some("Villager"));
+    frontend.renderer.print(Layer::Inspect, 1, 1, &person.name, WHITE);
+    let stage = if person.is_adult() { "adult" } else { "child" };
+    frontend.renderer.print(Layer::Inspect, 1, 2, &format!("Age {} ({})", person.age_in_years(), stage), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 3, &format!("Hunger {}/{}", person.hunger, Person::MAX_NEED), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 4, &format!("Rest {}/{}", person.rest, Person::MAX_NEED), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 5, &format!("Farming {} Gathering {}", person.skills.farming, person.skills.gathering), WHITE);
+    let mut house_query = <(&House, &Position)>::query();
+    let home = match person.home.and_then(|home| house_query.get(&game.world, home).ok()) {
+        Some((house, position)) => format!(
+            "Home {}, {} ({}/{})",
+            position.x - game.map.width(),
+            position.y - game.map.height(),
+            house.population(),
+            house.capacity
+        ),
+        None => String::from("Homeless"),
+    };
+    frontend.renderer.print(Layer::Inspect, 1, 6, &home, WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 7, &format!("Social {}/{}", person.social, Person::MAX_NEED), WHITE);
+    let mut workshop_query = <&Workshop>::query();
+    let job = match person.job {
+        Some(Job::Gatherer(id)) => {
+            let kind = game.resources.get(id);
+            let trade = match kind.method {
+                GatherMethod::Farm => "Farmer",
+                GatherMethod::Hunt => "Hunter",
+                _ => "Gatherer",
+            };
+            format!("{} ({})", trade, kind.name)
+        }
+        Some(Job::Crafter(building)) => match workshop_query.get(&game.world, building) {
+            Ok(workshop) => game.recipes.workshops[workshop.kind].name.clone(),
+            Err(_) => String::from("None"),
+        },
+        Some(Job::Militia(_)) => String::from("Militia"),
+        Some(Job::Scholar(_)) => String::from("Scholar"),
+        Some(Job::Labourer(_)) => String::from("Labourer"),
+        None => String::from("None"),
+    };
+    frontend.renderer.print(Layer::Inspect, 1, 8, &format!("Job {}, carrying {}/{}", job, person.carrying.total(), CARRY_CAPACITY), WHITE);
+
+    let mut intent_query = <&Intent>::query();
+    if let Ok(intent) = intent_query.get(&game.world, entity) {
+        frontend.renderer.print(Layer::Inspect, 1, 10, &format!("Doing: {}", intent.activity.name()), WHITE);
+        for (row, (activity, score)) in intent.scores.iter().enumerate() {
+            frontend.renderer.print(Layer::Inspect, 3, 11 + row as i32, &format!("{:<10} {}", activity.name(), score), WHITE);
+        }
+    }
+}
+
+fn render_workshop(frontend: &mut Frontend, game: &Game, workshop: &Workshop) {
+    let kind = &game.recipes.workshops[workshop.kind];
+    let recipe = &kind.recipes[workshop.recipe];
+    frontend.renderer.print_frame(Layer::Inspect, (0, 0), (INSPECT_WIDTH, INSPECT_HEIGHT), Some(kind.name.as_str()));
+    frontend.renderer.print(Layer::Inspect, 1, 1, &format!("Making {} (r to change)", recipe.name), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 2, &format!("In:  {}", describe_amounts(&game.resources, &recipe.inputs)), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 3, &format!("Out: {}", describe_amounts(&game.resources, &recipe.outputs)), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 4, &format!("Progress {}%", workshop.progress * 100 / recipe.time), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, 6, &format!("Workers {}/{}", workshop.workers.len(), kind.workers), WHITE);
+    let mut person_query = <&Person>::query();
+    for (row, worker) in workshop.workers.iter().enumerate() {
+        if let Ok(person) = person_query.get(&game.world, *worker) {
+            frontend.renderer.print(Layer::Inspect, 3, 7 + row as i32, &person.name, WHITE);
+        }
+    }
+
+    let row = 8 + kind.workers as i32;
+    frontend.renderer.print(Layer::Inspect, 1, row, "Input buffer", WHITE);
+    frontend.renderer.print(Layer::Inspect, 3, row + 1, &describe_inventory(&game.resources, &workshop.input), WHITE);
+    frontend.renderer.print(Layer::Inspect, 1, row + 3, &format!("Output buffer ({} max)", WORKSHOP_BUFFER), WHITE);
+    frontend.renderer.print(Layer::Inspect, 3, row + 4, &describe_inventory(&game.resources, &workshop.output), WHITE);
+}
+
+/// The start of a line of text, cut to fit the width of a column.
+fn clip(text: &str, width: i32) -> String {
+    return text.chars().take(width.max(0) as usize).collect();
+}
+
+fn describe_amounts(resources: &ResourceRegistry, amounts: &[(ResourceId, i32)]) -> String {
+    return amounts.iter()
+        .map(|(id, amount)| format!("{} {}", amount, resources.get(*id).name))
+        .collect::<Vec<String>>()
+        .join(", ");
+}
+
+fn describe_inventory(resources: &ResourceRegistry, inventory: &Inventory) -> String {
+    let amounts: Vec<(ResourceId, i32)> = inventory.iter().collect();
+    return if amounts.is_empty() { String::from("empty") } else { describe_amounts(resources, &amounts) };
+}
+
+fn handle_input(frontend: &mut Frontend, game: &Game) -> Vec<Action> {
     let mut actions = vec![];
-    let key_option = tcod.root.check_for_keypress(KEY_PRESSED);
-    match key_option {
-        Some(key) => match key {
-            Key {
-                code: Enter,
-                alt: true,
-                ..
-            } => actions.push(FullScreen),
-            Key { code: Escape, .. } => actions.push(Quit),
-            Key { code: Up, .. } => actions.push(MoveUp),
-            Key { code: Down, .. } => actions.push(MoveDown),
-            Key { code: Left, .. } => actions.push(MoveLeft),
-            Key { code: Right, .. } => actions.push(MoveRight),
-            Key { code: Spacebar, .. } => actions.push(Build),
+    match frontend.poll() {
+        Some(Event::Key { key, alt }) => match key {
+            Key::Enter if alt => actions.push(FullScreen),
+            Key::Escape => actions.push(Action::Pause),
+            Key::Up => actions.push(MoveUp),
+            Key::Down => actions.push(MoveDown),
+            Key::Left => actions.push(MoveLeft),
+            Key::Right => actions.push(MoveRight),
+            Key::Space => actions.push(Build),
+            Key::Char(character) => match character {
+                't' => actions.push(BuildWatchtower),
+                'g' => actions.push(BuildStockpile),
+                'k' => actions.push(BuildWorkshop),
+                'b' => actions.push(BuildBarracks),
+                'o' => actions.push(BuildGuardTower),
+                'l' => actions.push(BuildLibrary),
+                'u' => actions.push(OpenResearch),
+                'c' => actions.push(OrderEarthwork(Improvement::ClearForest)),
+                'e' => actions.push(OrderEarthwork(Improvement::Road)),
+                'i' => actions.push(OrderEarthwork(Improvement::Irrigation)),
+                'q' => actions.push(OrderEarthwork(Improvement::Tunnel)),
+                'n' => actions.push(NextWorkshop),
+                'r' => actions.push(NextRecipe),
+                'p' => actions.push(SendGift),
+                'm' => actions.push(ToggleMinimap),
+                'w' => actions.push(ScrollCamera(0, -CAMERA_SCROLL_SPEED)),
+                's' => actions.push(ScrollCamera(0, CAMERA_SCROLL_SPEED)),
+                'a' => actions.push(ScrollCamera(-CAMERA_SCROLL_SPEED, 0)),
+                'd' => actions.push(ScrollCamera(CAMERA_SCROLL_SPEED, 0)),
+                'f' => actions.push(FollowPlayer),
+                'v' => actions.push(FollowVillager),
+                'h' => actions.push(JumpToSettlement),
+                _ => { }
+            }
             _ => { }
         }
-        _ => { }
+        Some(Event::Mouse(mouse)) => {
+            let (x, y) = (mouse.x, mouse.y);
+            if mouse.left_pressed && game.minimap.contains(x, y) {
+                let target = game.minimap.to_world(x, y);
+                actions.push(MoveCamera(target.x, target.y));
+            } else if mouse.left_pressed && y >= PANEL_HEIGHT {
+                actions.push(Inspect(game.camera.left() + x, game.camera.top() + y));
+            }
+        }
+        None => { }
     };
     return actions
 }
@@ -192,13 +452,56 @@ fn surrounded_by_land(x: i32, y: i32, map: &GameMap) -> bool {
     return true;
 }
 
-fn process_player_action(action: Action, mut game: &mut Game) {
+fn follow_player(game: &mut Game) {
+    let mut query = <(Entity, &Player)>::query();
+    let player = *query.iter(&game.world).next().unwrap().0;
+    game.camera.follow(player);
+}
+
+fn building_cost(game: &Game, amount: i32) -> Vec<(ResourceId, i32)> {
+    return vec![(game.resources.id(BUILDING_MATERIAL), amount)];
+}
+
+fn player_position(game: &Game) -> Position {
+    let mut query = <(&Player, &Position)>::query();
+    return *query.iter(&game.world).next().unwrap().1;
+}
+
+/// Raises a building at `site` if the map allows it there and the colony can pay for it,
+/// with `spawn` pushing the building itself.
+fn try_build(game: &mut Game, site: Position, buildable: fn(&GameMap, i32, i32) -> bool, cost: &[(ResourceId, i32)], spawn: impl FnOnce(&mut World, Position)) {
+    if buildable(&game.map, site.x, site.y) && take_resources(&mut game.world, Faction::PLAYER, site, cost) {
+        game.map.make_tile_built_on(site.x, site.y);
+        game.stats.buildings_built += 1;
+        spawn(&mut game.world, site);
+    }
+}
+
+/// Whether the colony has researched what it needs to build something, telling the player
+/// when it has not.
+fn researched(game: &mut Game, unlock: Unlock, name: &str) -> bool {
+    if game.research.is_unlocked(unlock) {
+        return true;
+    }
+    game.messages.add(format!("Building a {} has not been researched yet", name), COLOR_UNAFFORDABLE);
+    return false;
+}
+
+fn process_player_action(action: Action, game: &mut Game) {
+    let mut query = <&Player>::query();
+    let alive = query.iter(&game.world).next().is_some_and(|player| player.alive);
+    if !alive {
+        if let MoveUp | MoveDown | MoveLeft | MoveRight | Build | BuildWatchtower | BuildStockpile | BuildWorkshop | BuildBarracks | BuildGuardTower | BuildLibrary | OrderEarthwork(_) | SendGift = action {
+            return;
+        }
+    }
+    let (width, height) = (game.map.width(), game.map.height());
     match action {
         MoveUp => {
             let mut query = <(&Player, &mut Position)>::query();
             let position = query.iter_mut(&mut game.world).next().unwrap().1;
             position.y -= 1;
-            if position.y < MAP_HEIGHT { position.y = MAP_HEIGHT*2 -1 }
+            if position.y < height { position.y = height*2 -1 }
             let (x, y) = (position.x, position.y);
             if game.map.is_tile_blocked(x, y) {
                 position.y += 1;
@@ -208,7 +511,7 @@ fn process_player_action(action: Action, mut game: &mut Game) {
             let mut query = <(&Player, &mut Position)>::query();
             let position = query.iter_mut(&mut game.world).next().unwrap().1;
             position.y += 1;
-            if position.y >= MAP_HEIGHT*2 { position.y = 0 + MAP_HEIGHT}
+            if position.y >= height*2 { position.y = 0 + height}
             if game.map.is_tile_blocked(position.x, position.y) {
                 position.y -= 1;
             }
@@ -217,7 +520,7 @@ fn process_player_action(action: Action, mut game: &mut Game) {
             let mut query = <(&Player, &mut Position)>::query();
             let position = query.iter_mut(&mut game.world).next().unwrap().1;
             position.x -= 1;
-            if position.x < MAP_WIDTH { position.x = MAP_WIDTH*2 -1 }
+            if position.x < width { position.x = width*2 -1 }
             let (x, y) = (position.x, position.y);
             if game.map.is_tile_blocked(x, y) {
                 position.x += 1;
@@ -227,30 +530,315 @@ fn process_player_action(action: Action, mut game: &mut Game) {
             let mut query = <(&Player, &mut Position)>::query();
             let position = query.iter_mut(&mut game.world).next().unwrap().1;
             position.x += 1;
-            if position.x >= MAP_WIDTH*2 { position.x = 0 + MAP_WIDTH}
+            if position.x >= width*2 { position.x = 0 + width}
             if game.map.is_tile_blocked(position.x, position.y) {
                 position.x -= 1;
             }
         }
         Build => {
-            let mut query = <(&Player,&Position)>::query();
-            let player = query.iter(&game.world).next().unwrap();
-            let player_pos = Position{ x: player.1.x, y: player.1.y };
-            if game.map.is_buildable(player_pos.x, player_pos.y) && game.wood >= 10 {
-                game.wood -= 10;
-                game.map.make_tile_built_on(player_pos.x, player_pos.y);
-                game.world.push((
-                    Position::new(player_pos.x, player_pos.y),
-                    Drawable::new('A',COLOR_VILLAGE),
-                    House::new()
-                ));
+            let site = player_position(game);
+            let cost = building_cost(game, HOUSE_COST);
+            try_build(game, site, GameMap::is_buildable, &cost, |world, site| {
+                world.push((site, Drawable::new('A', COLOR_VILLAGE), Vision::new(HOUSE_VISION_RADIUS), House::new(), Faction::PLAYER));
+            });
+        }
+        BuildWatchtower => {
+            if !researched(game, Unlock::Building(Building::Watchtower), Building::Watchtower.name()) {
+                return;
+            }
+            let player_pos = player_position(game);
+            // the player cannot climb hills, so prefer raising the tower on a neighbouring one
+            let mut site = player_pos;
+            for (x_offset, y_offset) in &[(0, -1), (0, 1), (-1, 0), (1, 0)] {
+                let (x, y) = (player_pos.x + x_offset, player_pos.y + y_offset);
+                if game.map.elevation(x, y) == HILL_ELEVATION_BONUS && game.map.is_watchtower_buildable(x, y) {
+                    site = Position::new(x, y);
+                    break;
+                }
+            }
+            let cost = building_cost(game, WATCHTOWER_COST);
+            try_build(game, site, GameMap::is_watchtower_buildable, &cost, |world, site| {
+                let vision = Vision::new(WATCHTOWER_VISION_RADIUS).with_algorithm(WATCHTOWER_FOV_ALGO);
+                world.push((site, Drawable::new('T', COLOR_WATCHTOWER), vision, Watchtower, Faction::PLAYER));
+            });
+        }
+        BuildStockpile => {
+            if !researched(game, Unlock::Building(Building::Stockpile), Building::Stockpile.name()) {
+                return;
+            }
+            let site = player_position(game);
+            let cost = building_cost(game, STOCKPILE_COST);
+            try_build(game, site, GameMap::is_buildable, &cost, |world, site| {
+                world.push((site, Drawable::new('=', COLOR_STOCKPILE), Stockpile::new(Inventory::new()), Faction::PLAYER));
+            });
+        }
+        BuildWorkshop => {
+            let kind = game.selected_workshop;
+            let name = game.recipes.workshops[kind].name.to_lowercase();
+            if !researched(game, Unlock::Workshop(kind), &name) {
+                return;
+            }
+            let site = player_position(game);
+            let glyph = game.recipes.workshops[kind].glyph;
+            let cost = game.recipes.workshops[kind].cost.clone();
+            try_build(game, site, GameMap::is_buildable, &cost, |world, site| {
+                world.push((site, Drawable::new(glyph, COLOR_WORKSHOP), Workshop::new(kind), Faction::PLAYER));
+            });
+        }
+        BuildBarracks => {
+            if !researched(game, Unlock::Building(Building::Barracks), Building::Barracks.name()) {
+                return;
+            }
+            let site = player_position(game);
+            let cost = building_cost(game, BARRACKS_COST);
+            try_build(game, site, GameMap::is_buildable, &cost, |world, site| {
+                world.push((site, Drawable::new('M', COLOR_DEFENCE), Barracks::new(), Faction::PLAYER));
+            });
+        }
+        BuildGuardTower => {
+            if !researched(game, Unlock::Building(Building::GuardTower), Building::GuardTower.name()) {
+                return;
+            }
+            let site = player_position(game);
+            let cost = building_cost(game, GUARD_TOWER_COST);
+            try_build(game, site, GameMap::is_buildable, &cost, |world, site| {
+                world.push((site, Drawable::new('Y', COLOR_DEFENCE), Vision::new(GUARD_TOWER_RANGE), GuardTower::new(), Faction::PLAYER));
+            });
+        }
+        BuildLibrary => {
+            if !researched(game, Unlock::Building(Building::Library), Building::Library.name()) {
+                return;
+            }
+            let site = player_position(game);
+            let cost = building_cost(game, LIBRARY_COST);
+            try_build(game, site, GameMap::is_buildable, &cost, |world, site| {
+                world.push((site, Drawable::new('L', COLOR_LIBRARY), Library::new(), Faction::PLAYER));
+            });
+        }
+        OrderEarthwork(improvement) => order_earthwork(game, improvement),
+        NextWorkshop => {
+            // workshops the colony has yet to research are skipped over
+            let count = game.recipes.workshops.len();
+            let research = &game.research;
+            let next = (1..=count)
+                .map(|offset| (game.selected_workshop + offset) % count)
+                .find(|kind| research.is_unlocked(Unlock::Workshop(*kind)));
+            game.selected_workshop = next.unwrap_or(game.selected_workshop);
+        }
+        SendGift => {
+            let player_pos = player_position(game);
+            send_gift(game, player_pos);
+        }
+        NextRecipe => {
+            let mut query = <&mut Workshop>::query();
+            let world = &mut game.world;
+            if let Some(Ok(workshop)) = game.inspected.map(|entity| query.get_mut(world, entity)) {
+                let recipes = game.recipes.workshops[workshop.kind].recipes.len();
+                let (kind, research) = (workshop.kind, &game.research);
+                let next = (1..=recipes)
+                    .map(|offset| (workshop.recipe + offset) % recipes)
+                    .find(|recipe| research.is_unlocked(Unlock::Recipe(kind, *recipe)));
+                if let Some(recipe) = next.filter(|recipe| *recipe != workshop.recipe) {
+                    workshop.set_recipe(recipe);
+                }
+            }
+        }
+        ToggleMinimap => game.minimap.toggle(),
+        MoveCamera(x, y) => game.camera.jump_to(x, y),
+        ScrollCamera(x_delta, y_delta) => game.camera.scroll(x_delta, y_delta),
+        Inspect(x, y) => {
+            let mut query = <(Entity, &Person, &Position)>::query();
+            let villager = query.iter(&game.world)
+                .find(|(_, _, position)| position.x == x && position.y == y)
+                .map(|(entity, _, _)| *entity);
+            let mut query = <(Entity, &Workshop, &Position)>::query();
+            let workshop = query.iter(&game.world)
+                .find(|(_, _, position)| position.x == x && position.y == y)
+                .map(|(entity, _, _)| *entity);
+            game.inspected = villager.or(workshop);
+        }
+        FollowPlayer => follow_player(game),
+        FollowVillager => {
+            let mut query = <(Entity, &Person)>::query();
+            let villagers: Vec<Entity> = query.iter(&game.world).map(|(entity, _)| *entity).collect();
+            if !villagers.is_empty() {
+                game.camera.followed_villager = (game.camera.followed_villager + 1) % villagers.len();
+                game.camera.follow(villagers[game.camera.followed_villager]);
+            }
+        }
+        JumpToSettlement => {
+            let mut query = <(&House, &Position)>::query();
+            let settlements: Vec<Position> = query.iter(&game.world).map(|(_, position)| *position).collect();
+            if !settlements.is_empty() {
+                game.camera.visited_settlement = (game.camera.visited_settlement + 1) % settlements.len();
+                let settlement = settlements[game.camera.visited_settlement];
+                game.camera.jump_to(settlement.x, settlement.y);
             }
         }
         _ => {}
     }
+
+    if let MoveUp | MoveDown | MoveLeft | MoveRight = action {
+        follow_player(game);
+    }
 }
 
+/// Generates a new world from the chosen options, lands the player on it and founds the
+/// neighbouring settlements.
+fn new_game(frontend: &mut Frontend, options: &NewGame) -> Game {
+    let (width, height) = frontend.renderer.screen_size();
+    let (map_width, map_height) = options.map_size.dimensions();
+    let resources = ResourceRegistry::load();
+    let recipes = RecipeBook::load(&resources);
+    let events = Events::load(&resources, options.rng_seed(5));
+    let research = Research::load(&recipes);
+    let mut map = make_map(options);
+    let visibility = Visibility::new(&mut map);
+    let mut game = Game {
+        map,
+        camera: Camera::new(width, height, map_width, map_height),
+        minimap: Minimap::new(width, map_width, map_height),
+        visibility,
+        calendar: Calendar::new(),
+        weather: Weather::new(options.rng_seed(0), map_width, map_height),
+        settlements: Settlements::new(options.rng_seed(2)),
+        raids: Raids::new(options.rng_seed(4)),
+        events,
+        wildlife: Wildlife::new(options.rng_seed(3)),
+        messages: Messages::new(),
+        inspected: None,
+        population: 0,
+        stats: RunStats::default(),
+        difficulty: options.difficulty,
+        resources,
+        recipes,
+        research,
+        stock: Inventory::new(),
+        selected_workshop: 0,
+        world: World::default(),
+    };
+
+    // the landing is drawn from the world seed too, so a seed always replays the same run
+    let mut rng = StdRng::seed_from_u64(options.rng_seed(6));
+    let player = loop {
+        let x = rng.gen_range(0, map_width);
+        let y = rng.gen_range(0, map_height);
+        if surrounded_by_land(x, y, &game.map) {
+            break game.world.push((
+                Position::new(x + map_width, y + map_height),
+                Drawable::new('@', WHITE),
+                Vision::new(TORCH_RADIUS),
+                Player::new(true),
+                Faction::PLAYER
+            ))
+        }
+    };
+    arm(&mut game.world, player, PLAYER_HEALTH, PLAYER_ATTACK, PLAYER_ARMOUR);
+    game.camera.follow(player);
+
+    // the colony starts with a stockpile of supplies where the player lands
+    let mut query = <&Position>::query();
+    let landing = *query.get(&game.world, player).unwrap();
+    game.map.make_tile_built_on(landing.x, landing.y);
+    let supplies: Vec<(ResourceId, i32)> = game.resources.starting_inventory().iter()
+        .map(|(id, amount)| (id, amount * game.difficulty.supplies_percent() / 100))
+        .collect();
+    game.world.push((landing, Drawable::new('=', COLOR_STOCKPILE), Stockpile::new(Inventory::new()), Faction::PLAYER));
+    store_goods(&mut game, landing, &supplies);
+    game.stock = total_stock(&game.world, Faction::PLAYER);
+    spawn_rivals(&mut game, landing);
+    game.wildlife.populate(&game.map, &mut game.world);
+
+    // the map layers are sized for the world they show
+    frontend.renderer.create_layer(Layer::Map, map_width*3, map_height*3);
+    let (minimap_width, minimap_height) = game.minimap.console_size();
+    frontend.renderer.create_layer(Layer::Minimap, minimap_width, minimap_height);
+    frontend.render_cache = RenderCache::new(map_width*3, map_height*3);
+    return game;
+}
+
+/// The run is over once the player has died, or once every villager is gone and there is
+/// no food left to raise new ones.
+fn check_game_over(game: &Game) -> Option<Outcome> {
+    let mut query = <&Player>::query();
+    if !query.iter(&game.world).all(|player| player.alive) {
+        return Some(Outcome::PlayerDied);
+    }
+    let food_left = game.resources.edible().iter().any(|id| game.stock.get(*id) > 0);
+    if game.population == 0 && game.stats.peak_population > 0 && !food_left {
+        return Some(Outcome::ColonyDied);
+    }
+    return None;
+}
+
+/// Draws the game as the camera sees it.
+fn render_game(frontend: &mut Frontend, game: &mut Game) {
+    update_visibility(frontend, game);
+    game.camera.update(&game.world);
+    render_all(frontend, game);
+}
+
+/// Handles the player's input and steps the simulation by the time since the last frame,
+/// returning why the game stopped if it did.
+fn play_frame(frontend: &mut Frontend, session: &mut Session, preferences: &mut Preferences) -> Option<Outcome> {
+    let time_of_current_frame = frontend.input.elapsed_millis();
+    let time_delta = time_of_current_frame - session.time_of_last_frame;
+    session.time_of_last_frame = time_of_current_frame;
+    let game = &mut session.game;
+    let rng = &mut session.rng;
+    if game.events.pending.is_some() {
+        return Some(Outcome::Event);
+    }
+
+    let actions = handle_input(frontend, game);
+    for action in actions {
+        if action == FullScreen {
+            preferences.fullscreen = !preferences.fullscreen;
+            frontend.renderer.set_fullscreen(preferences.fullscreen);
+        } else if action == Pause {
+            return Some(Outcome::Paused);
+        } else if action == OpenResearch {
+            return Some(Outcome::Research);
+        }
+        process_player_action(action, game);
+    }
+    if preferences.edge_scrolling {
+        // the mouse is nowhere until it has moved, so the camera stays put at startup
+        if let Some(mouse) = frontend.mouse {
+            game.camera.edge_scroll(mouse.x, mouse.y, time_delta);
+        }
+    }
+
+    let new_day = game.calendar.advance(time_delta);
+    if new_day {
+        game.weather.daily_update(&mut game.map, &mut game.world, game.calendar.season);
+        daily_needs_system(game, rng);
+        household_system(game);
+        settlements_system(game);
+        raid_system(game);
+        event_system(game);
+        healing_system(&mut game.world);
+        game.wildlife.daily_update(&game.map, &mut game.world);
+    }
+    animal_system(&game.map, &mut game.wildlife, &mut game.world, time_delta);
+    housing_system(game, rng, time_delta);
+    labour_system(game);
+    ai_system(game, rng, time_delta);
+    raider_system(game, time_delta);
+    guard_tower_system(game, time_delta);
+    combat_system(game, rng, time_delta);
+    caravan_system(game, time_delta, new_day);
+    game.stock = total_stock(&game.world, Faction::PLAYER);
+
+    return check_game_over(game);
+}
+
+#[cfg(feature = "tcod")]
 fn main() {
+    use tcod::console::{FontLayout, FontType, Root};
+    use crate::tcod_renderer::tcod_renderer::{TcodInput, TcodRenderer};
+    use crate::states::states::State;
+
     tcod::system::set_fps(LIMIT_FPS);
     let (screen_width, screen_height) = tcod::system::get_current_resolution();
     let pixel_width = screen_width / 20;
@@ -263,138 +851,295 @@ fn main() {
         .title("Rouge Civ")
         .init();
 
-    let mut tcod = Tcod {
-        root,
-        con: Offscreen::new(MAP_WIDTH*3, MAP_HEIGHT*3),
-        panel: Offscreen::new(screen_width, PANEL_HEIGHT),
-        fov: FovMap::new(MAP_WIDTH*3, MAP_HEIGHT*3),
+    // the map layers are created for each world in `new_game`
+    let mut renderer = TcodRenderer::new(root);
+    renderer.create_layer(Layer::Panel, pixel_width, PANEL_HEIGHT);
+    renderer.create_layer(Layer::Night, pixel_width, pixel_height);
+    renderer.create_layer(Layer::Inspect, INSPECT_WIDTH, INSPECT_HEIGHT);
+    renderer.create_layer(Layer::Popup, EVENT_WIDTH, EVENT_HEIGHT);
+    let mut frontend = Frontend {
+        renderer: Box::new(renderer),
+        input: Box::new(TcodInput),
+        mouse: None,
+        render_cache: RenderCache::new(1, 1),
     };
 
+    let mut preferences = Preferences::new();
+    let mut state = State::main_menu();
+    while !frontend.renderer.is_closed() {
+        state = state.step(&mut frontend, &mut preferences);
+        if let State::Exit = state {
+            break;
+        }
+    }
+}
 
-    let mut game = Game {
-        map: make_map(),
-        camera_width: pixel_width,
-        population: 0,
-        wood: 100,
-        food: 100,
-        camera_height: pixel_height,
-        world: World::default(),
-    };
+#[cfg(not(feature = "tcod"))]
+fn main() {
+    eprintln!("Rouge Civ was built without a display. Build it with the tcod feature to play.");
+}
 
-    let player = loop {
-        let mut rng = rand::thread_rng();
-        let x = rng.gen_range(0, MAP_WIDTH);
-        let y = rng.gen_range(0, MAP_HEIGHT);
-        if surrounded_by_land(x, y, &game.map) {
-            break game.world.push((
-                Position::new(x + MAP_WIDTH, y + MAP_HEIGHT),
-                Drawable::new('@', WHITE),
-                Vision::new(true),
-                Player::new(true)
-            ))
-        }
-    };
+/// Ages every villager by a day and grows their needs. Villagers die of starvation once their
+/// hunger is full, and become ever more likely to die of old age past `OLD_AGE`.
+fn daily_needs_system(game: &mut Game, rng: &mut StdRng) {
+    let mut deaths = Vec::new();
+    let mut person_query = <(Entity, &mut Person, &Faction)>::query();
+    for (entity, person, faction) in person_query.iter_mut(&mut game.world) {
+        person.age += 1;
+        person.hunger = (person.hunger + HUNGER_PER_DAY).min(Person::MAX_NEED);
+        person.social = (person.social + SOCIAL_PER_DAY).min(Person::MAX_NEED);
 
-    for y in 0..MAP_HEIGHT*3 {
-        for x in 0..MAP_WIDTH*3 {
-            tcod.fov.set(
-                x ,
-                y,
-                !game.map.is_tile_blocking_vision(x as usize,y as usize),
-                !game.map.is_tile_blocked(x,y),
-            );
+        let starved = person.hunger >= Person::MAX_NEED;
+        let years_past_old_age = person.age_in_years() - OLD_AGE;
+        let died_of_old_age = years_past_old_age >= 0 && rng.gen_range(0, 100) <= years_past_old_age;
+        if starved || died_of_old_age {
+            deaths.push(*entity);
+            if *faction == Faction::PLAYER {
+                let cause = if starved { "starved to death" } else { "died of old age" };
+                game.messages.add(format!("{} {}", person.name, cause), COLOR_DEATH);
+            }
         }
     }
 
-    let previous_player_position = (-1, -1);
-
-    let mut rng = rand::thread_rng();
+    for entity in deaths {
+        remove_person(&mut game.world, entity);
+    }
+}
 
-    let mut time_of_last_frame = 0;
-    'game_loop: while !tcod.root.window_closed() {
-        let time_of_current_frame = get_elapsed_time().as_millis();
-        let time_delta = time_of_current_frame - time_of_last_frame;
-        time_of_last_frame = time_of_current_frame;
+/// Grown children move out of full houses into a house with room to spare in the same
+/// settlement, so their parents' house has space for more births.
+fn household_system(game: &mut Game) {
+    let mut person_query = <(Entity, &Person)>::query();
+    let adults: HashSet<Entity> = person_query.iter(&game.world)
+        .filter(|(_, person)| person.is_adult())
+        .map(|(entity, _)| *entity)
+        .collect();
 
-        // clear the screen of the previous frame
-        tcod.con.clear();
+    let mut house_query = <(Entity, &House, &Faction)>::query();
+    let mut vacancies: Vec<(Entity, Faction)> = house_query.iter(&game.world)
+        .filter(|(_, house, _)| !house.is_full())
+        .map(|(entity, _, faction)| (*entity, *faction))
+        .collect();
+    let leavers: Vec<(Entity, Entity, Faction)> = house_query.iter(&game.world)
+        .filter(|(_, house, _)| house.is_full())
+        .filter_map(|(entity, house, faction)| {
+            // the first two adults are the parents, anyone after them is a grown child
+            house.residents.iter()
+                .filter(|resident| adults.contains(resident))
+                .nth(2)
+                .map(|resident| (*resident, *entity, *faction))
+        })
+        .collect();
 
-        let mut player_query = <(&Player,&Position)>::query();
-        let players_position = player_query.iter(&game.world).next().unwrap().1;
+    for (person, old_home, faction) in leavers {
+        let new_home = match vacancies.iter().rposition(|(_, vacancy)| *vacancy == faction) {
+            Some(index) => vacancies.remove(index).0,
+            None => continue,
+        };
+        let mut house_query = <&mut House>::query();
+        if let Ok(house) = house_query.get_mut(&mut game.world, old_home) {
+            house.residents.retain(|resident| *resident != person);
+        }
+        if let Ok(house) = house_query.get_mut(&mut game.world, new_home) {
+            house.residents.push(person);
+            if !house.is_full() {
+                vacancies.push((new_home, faction));
+            }
+        }
+        let mut person_query = <&mut Person>::query();
+        if let Ok(person) = person_query.get_mut(&mut game.world, person) {
+            person.home = Some(new_home);
+        }
+    }
+}
 
-        let fov_recompute = previous_player_position != (players_position.x, players_position.y);
-        render_all(&mut tcod, &mut game, fov_recompute, player);
-        tcod.root.flush();
+/// Fills houses with residents. A house with fewer than two adults takes in a homeless adult
+/// of its settlement or, failing that, a new settler. Once two adults live in a house they
+/// have children until the house is full.
+fn housing_system(game: &mut Game, rng: &mut StdRng, time_delta: u128) {
+    let mut person_query = <(Entity, &Person, &Faction)>::query();
+    let adults: HashSet<Entity> = person_query.iter(&game.world)
+        .filter(|(_, person, _)| person.is_adult())
+        .map(|(entity, _, _)| *entity)
+        .collect();
+    let mut homeless: Vec<(Entity, Faction)> = person_query.iter(&game.world)
+        .filter(|(_, person, _)| person.is_adult() && person.home.is_none())
+        .map(|(entity, _, faction)| (*entity, *faction))
+        .collect();
+    game.population = person_query.iter(&game.world)
+        .filter(|(_, _, faction)| **faction == Faction::PLAYER)
+        .count() as i32;
+    game.stats.peak_population = game.stats.peak_population.max(game.population);
 
-        let actions = handle_keys(&mut tcod);
-        for action in actions {
-            if action == FullScreen {
-                let fullscreen = tcod.root.is_fullscreen();
-                tcod.root.set_fullscreen(!fullscreen);
-            } else if action == Quit { break 'game_loop }
-            process_player_action(action, &mut game);
+    let mut houses_query = <(Entity, &mut House, &Position, &Faction)>::query();
+    let mut stockpiles = load_stockpiles(&game.world);
+    let edible = game.resources.edible();
+    let mut new_people = Vec::new();
+    let mut moving_in = Vec::new();
+    for (entity, house, position, faction) in houses_query.iter_mut(&mut game.world) {
+        house.time_since_last_spawn += time_delta;
+        if house.is_full() || house.time_since_last_spawn < House::TIME_BETWEEN_SPAWNS {
+            continue;
+        }
+        let adult_residents = house.residents.iter().filter(|resident| adults.contains(resident)).count();
+        if adult_residents < 2 {
+            if let Some(index) = homeless.iter().rposition(|(_, homeless_faction)| homeless_faction == faction) {
+                let person = homeless.remove(index).0;
+                house.time_since_last_spawn = 0;
+                house.residents.push(person);
+                moving_in.push((person, *entity));
+                continue;
+            }
         }
+        // the food for each new villager comes out of the stockpile nearest their house
+        let store = match nearest(&stockpiles, *faction, *position, |stockpile| stockpile.first_of(&edible, BIRTH_COST).is_some()) {
+            Some(index) => index,
+            None => continue,
+        };
+        // a house hemmed in on every side has nowhere to put anyone new
+        let map = &game.map;
+        let sites: Vec<(i32, i32)> = (-BIRTH_RADIUS..=BIRTH_RADIUS)
+            .flat_map(|y_offset| (-BIRTH_RADIUS..=BIRTH_RADIUS).map(move |x_offset| (position.x + x_offset, position.y + y_offset)))
+            .filter(|(x, y)| map.is_buildable(*x, *y))
+            .collect();
+        if sites.is_empty() {
+            continue;
+        }
+        house.time_since_last_spawn = 0;
+        let stockpile = &mut stockpiles[store].stockpile;
+        if let Some(meal) = stockpile.first_of(&edible, BIRTH_COST) {
+            stockpile.inventory.take(meal, BIRTH_COST);
+        }
+        let (x, y) = sites[rng.gen_range(0, sites.len())];
+        // settlers arrive grown up, everyone else is born into the house
+        let age = if adult_residents < 2 { ADULT_AGE * DAYS_PER_YEAR } else { 0 };
+        // the first resident of every house in the colony scouts the surroundings
+        let scout = house.residents.is_empty() && *faction == Faction::PLAYER;
+        let person = Person::new(Some(*entity), generate_name(rng), age);
+        new_people.push((*entity, person, Position::new(x, y), *faction, scout));
+    }
 
-        housing_system(&mut game, &mut rng, time_delta);
-        person_system(&mut game, &mut rng, time_delta);
-        harvest_system(&mut game, time_delta)
+    store_stockpiles(&mut game.world, &stockpiles);
+    for (person, home) in moving_in {
+        let mut query = <&mut Person>::query();
+        if let Ok(person) = query.get_mut(&mut game.world, person) {
+            person.home = Some(home);
+        }
+    }
+    for (home, person, position, faction, scout) in new_people {
+        let color = if faction == Faction::PLAYER { COLOR_PERSON } else { COLOR_RIVAL };
+        let drawable = Drawable::new('&', color);
+        let entity = if scout {
+            game.world.push((person, position, drawable, Intent::new(), faction, Vision::new(SCOUT_VISION_RADIUS), Scout))
+        } else {
+            game.world.push((person, position, drawable, Intent::new(), faction))
+        };
+        arm(&mut game.world, entity, PERSON_HEALTH, PERSON_ATTACK, 0);
+        let mut query = <&mut House>::query();
+        if let Ok(house) = query.get_mut(&mut game.world, home) {
+            house.residents.push(entity);
+        }
     }
 }
 
-fn harvest_system(mut game: &mut Game, time_delta: u128) {
-    let mut person_query = <(&mut Person, &mut Position)>::query();
-    for (person, position) in person_query.iter_mut(&mut game.world) {
-        person.time_since_last_harvest += time_delta;
-        if person.time_since_last_harvest >= Person::TIME_BETWEEN_ACTIONS {
-            person.time_since_last_harvest = 0;
-            let harvest = game.map.harvest(position.x, position.y);
-            game.food += harvest;
+/// Hands out jobs within each settlement. Workshops with free worker slots are staffed first,
+/// by adults without a job or by gatherers of resources their settlement already has its
+/// target of. Every other adult without a job gathers the resource their settlement is
+/// shortest of, measured against its target stock and counting what the current workers
+/// will bring in.
+fn labour_system(game: &mut Game) {
+    let mut query = <(&Person, &Faction)>::query();
+    let factions: HashSet<Faction> = query.iter(&game.world).map(|(_, faction)| *faction).collect();
+    let stocks: HashMap<Faction, Inventory> = factions.iter()
+        .map(|faction| (*faction, total_stock(&game.world, *faction)))
+        .collect();
+    staff_workshops(game, &stocks);
+    muster_militia(game, &stocks);
+    staff_libraries(game, &stocks);
+    staff_earthworks(game, &stocks);
+
+    let mut workers: HashMap<(Faction, ResourceId), i32> = HashMap::new();
+    for (person, faction) in query.iter(&game.world) {
+        if let Some(Job::Gatherer(id)) = person.job {
+            *workers.entry((*faction, id)).or_insert(0) += 1;
+        }
+    }
+    let gathered: Vec<ResourceId> = game.resources.ids()
+        .filter(|id| game.resources.get(*id).method != GatherMethod::Craft && game.resources.get(*id).target > 0)
+        .collect();
+    if gathered.is_empty() {
+        return;
+    }
+
+    let resources = &game.resources;
+    let mut query = <(&mut Person, &Faction)>::query();
+    for (person, faction) in query.iter_mut(&mut game.world) {
+        if person.job.is_some() || !person.is_adult() {
+            continue;
         }
+        let shortage = |id: &ResourceId| {
+            let kind = resources.get(*id);
+            let expected = stocks[faction].get(*id) + workers.get(&(*faction, *id)).copied().unwrap_or(0) * CARRY_CAPACITY;
+            expected * 100 / kind.target
+        };
+        let id = *gathered.iter().min_by_key(|id| shortage(id)).unwrap();
+        *workers.entry((*faction, id)).or_insert(0) += 1;
+        person.job = Some(Job::Gatherer(id));
     }
 }
 
-fn person_system(game: &mut Game, rng: &mut ThreadRng, time_delta: u128) {
-    let mut person_query = <(&mut Person, &mut Position)>::query();
-    for (person, position) in person_query.iter_mut(&mut game.world) {
-        person.time_since_last_movement += time_delta;
-        if person.time_since_last_movement >= Person::TIME_BETWEEN_ACTIONS {
-            person.time_since_last_movement = 0;
-            let lower_bound_x = if person.home.x - position.x < 5  { -1 } else { 0 };
-            let upper_bound_x = if person.home.x - position.x > -5  { 2 } else { 1 };
-            let lower_bound_y = if person.home.y - position.y < 5  { -1 } else { 0 };
-            let upper_bound_y = if person.home.y - position.y > -5  { 2 } else { 1 };
-            let x_delta = rng.gen_range(lower_bound_x, upper_bound_x);
-            let y_delta = rng.gen_range(lower_bound_y, upper_bound_y);
-            position.x += x_delta;
-            position.y += y_delta;
+fn staff_workshops(game: &mut Game, stocks: &HashMap<Faction, Inventory>) {
+    // workers who died or moved on leave their slot free
+    let jobs = jobs(&game.world);
+    let mut standing = HashSet::new();
+    let mut vacancies = Vec::new();
+    let mut workshop_query = <(Entity, &mut Workshop, &Faction)>::query();
+    for (entity, workshop, faction) in workshop_query.iter_mut(&mut game.world) {
+        workshop.workers.retain(|worker| jobs.get(worker) == Some(&Some(Job::Crafter(*entity))));
+        let free = game.recipes.workshops[workshop.kind].workers - workshop.workers.len();
+        standing.insert(*entity);
+        vacancies.extend(std::iter::repeat_n((*entity, *faction), free));
+    }
+
+    let staffing = staff(game, stocks, &standing, vacancies, Job::Crafter);
+    let mut query = <&mut Workshop>::query();
+    for (workshop, worker) in staffing.hired {
+        if let Ok(workshop) = query.get_mut(&mut game.world, workshop) {
+            workshop.workers.push(worker);
         }
     }
 }
 
-fn housing_system(mut game: &mut Game, rng: &mut ThreadRng, time_delta: u128) {
-    let mut houses_query = <(&mut House, &Position)>::query();
-    let mut new_people = Vec::new();
-    for (house, position) in houses_query.iter_mut(&mut game.world) {
-        house.time_since_last_spawn += time_delta;
-        if house.population <= 4 && game.food >= 10 && house.time_since_last_spawn >= House::TIME_BETWEEN_SPAWNS {
-            house.time_since_last_spawn = 0;
-            house.population += 1;
-            game.food -= 10;
-            let (x, y) = loop {
-                let x = position.x + rng.gen_range(-3, 4);
-                let y = position.y + rng.gen_range(-3, 4);
-                if game.map.is_buildable(x, y) {
-                    break (x, y)
-                }
-            };
-            new_people.push((
-                Person::new(position.x, position.y),
-                Position::new(x, y),
-                Drawable::new('&', COLOR_PERSON)
-            ));
+/// Fills the colony's barracks with militia, taken from adults without a job or gatherers of
+/// resources the colony already has its target of. Militia hit harder and wear armour, and
+/// go back to ordinary work when their barracks is lost.
+fn muster_militia(game: &mut Game, stocks: &HashMap<Faction, Inventory>) {
+    let jobs = jobs(&game.world);
+    let mut standing = HashSet::new();
+    let mut vacancies = Vec::new();
+    let mut barracks_query = <(Entity, &mut Barracks)>::query();
+    for (entity, barracks) in barracks_query.iter_mut(&mut game.world) {
+        barracks.militia.retain(|member| jobs.get(member) == Some(&Some(Job::Militia(*entity))));
+        standing.insert(*entity);
+        vacancies.extend(std::iter::repeat_n((*entity, Faction::PLAYER), MILITIA_PER_BARRACKS - barracks.militia.len()));
+    }
+
+    let staffing = staff(game, stocks, &standing, vacancies, Job::Militia);
+    let mut query = <(&mut Attack, &mut Defense)>::query();
+    for member in staffing.dismissed {
+        if let Ok((attack, defense)) = query.get_mut(&mut game.world, member) {
+            attack.power = PERSON_ATTACK;
+            defense.armour = 0;
         }
     }
-    for person in new_people {
-        game.world.push(person);
+    for (barracks, recruit) in staffing.hired {
+        if let Ok((attack, defense)) = query.get_mut(&mut game.world, recruit) {
+            attack.power = MILITIA_ATTACK;
+            defense.armour = MILITIA_ARMOUR;
+        }
+        let mut barracks_query = <&mut Barracks>::query();
+        if let Ok(barracks) = barracks_query.get_mut(&mut game.world, barracks) {
+            barracks.militia.push(recruit);
+        }
     }
 }
@@ -0,0 +1,279 @@
+pub mod memory_renderer {
+    use std::collections::HashMap;
+    use std::collections::VecDeque;
+    use crate::renderer::renderer::{Color, Layer, Renderer, BLACK, WHITE};
+    use crate::input::input::{Event, Input};
+
+    /// Splits text into the rows `print_wrapped` puts it on, breaking between words.
+    fn wrap(text: &str, width: i32) -> Vec<String> {
+        let width = width.max(1) as usize;
+        let mut rows = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut row = String::new();
+            for word in paragraph.split_whitespace() {
+                if !row.is_empty() && row.chars().count() + 1 + word.chars().count() > width {
+                    rows.push(row);
+                    row = String::new();
+                }
+                if !row.is_empty() {
+                    row.push(' ');
+                }
+                row.push_str(word);
+            }
+            rows.push(row);
+        }
+        return rows;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Cell {
+        pub glyph: char,
+        pub foreground: Color,
+        pub background: Color,
+    }
+
+    const BLANK: Cell = Cell { glyph: ' ', foreground: WHITE, background: BLACK };
+
+    struct Grid {
+        width: i32,
+        height: i32,
+        cells: Vec<Cell>,
+    }
+
+    impl Grid {
+        fn new(width: i32, height: i32) -> Grid {
+            return Grid { width, height, cells: vec![BLANK; (width.max(0) * height.max(0)) as usize] };
+        }
+
+        fn cell(&mut self, x: i32, y: i32) -> Option<&mut Cell> {
+            if x < 0 || y < 0 || x >= self.width || y >= self.height {
+                return None;
+            }
+            return self.cells.get_mut((x + y * self.width) as usize);
+        }
+    }
+
+    fn blend(from: Color, to: Color, amount: f32) -> Color {
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount).round() as u8;
+        return Color { r: mix(from.r, to.r), g: mix(from.g, to.g), b: mix(from.b, to.b) };
+    }
+
+    /// Draws into grids of cells kept in memory, so what the game shows can be checked
+    /// without opening a window.
+    pub(crate) struct MemoryRenderer {
+        layers: HashMap<Layer, Grid>,
+    }
+
+    impl MemoryRenderer {
+        pub fn new(width: i32, height: i32) -> MemoryRenderer {
+            let mut layers = HashMap::new();
+            layers.insert(Layer::Screen, Grid::new(width, height));
+            return MemoryRenderer { layers };
+        }
+
+        fn grid(&mut self, layer: Layer) -> &mut Grid {
+            return self.layers.get_mut(&layer).unwrap_or_else(|| panic!("No {:?} layer", layer));
+        }
+
+        pub fn cell(&self, layer: Layer, x: i32, y: i32) -> Cell {
+            let grid = &self.layers[&layer];
+            return grid.cells[(x + y * grid.width) as usize];
+        }
+
+        /// The glyphs on the screen, one line per row.
+        pub fn snapshot(&self) -> String {
+            let screen = &self.layers[&Layer::Screen];
+            return screen.cells.chunks(screen.width.max(1) as usize)
+                .map(|row| row.iter().map(|cell| cell.glyph).collect::<String>())
+                .collect::<Vec<String>>()
+                .join("\n");
+        }
+    }
+
+    impl Renderer for MemoryRenderer {
+        fn screen_size(&self) -> (i32, i32) {
+            let screen = &self.layers[&Layer::Screen];
+            return (screen.width, screen.height);
+        }
+
+        fn create_layer(&mut self, layer: Layer, width: i32, height: i32) {
+            self.layers.insert(layer, Grid::new(width, height));
+        }
+
+        fn clear(&mut self, layer: Layer, background: Color) {
+            for cell in self.grid(layer).cells.iter_mut() {
+                *cell = Cell { background, ..BLANK };
+            }
+        }
+
+        fn put_glyph(&mut self, layer: Layer, x: i32, y: i32, glyph: char, color: Color) {
+            if let Some(cell) = self.grid(layer).cell(x, y) {
+                cell.glyph = glyph;
+                cell.foreground = color;
+            }
+        }
+
+        fn set_background(&mut self, layer: Layer, x: i32, y: i32, color: Color) {
+            if let Some(cell) = self.grid(layer).cell(x, y) {
+                cell.background = color;
+            }
+        }
+
+        fn print(&mut self, layer: Layer, x: i32, y: i32, text: &str, color: Color) {
+            for (offset, glyph) in text.chars().enumerate() {
+                self.put_glyph(layer, x + offset as i32, y, glyph, color);
+            }
+        }
+
+        fn print_centred(&mut self, layer: Layer, x: i32, y: i32, text: &str, color: Color) {
+            self.print(layer, x - text.chars().count() as i32 / 2, y, text, color);
+        }
+
+        fn print_wrapped(&mut self, layer: Layer, position: (i32, i32), size: (i32, i32), text: &str, color: Color) -> i32 {
+            let rows: Vec<String> = wrap(text, size.0).into_iter().take(size.1.max(0) as usize).collect();
+            for (row, line) in rows.iter().enumerate() {
+                self.print(layer, position.0, position.1 + row as i32, line, color);
+            }
+            return rows.len() as i32;
+        }
+
+        fn print_frame(&mut self, layer: Layer, position: (i32, i32), size: (i32, i32), title: Option<&str>) {
+            let (left, top) = position;
+            let (right, bottom) = (left + size.0 - 1, top + size.1 - 1);
+            let grid = self.grid(layer);
+            for y in top..=bottom {
+                for x in left..=right {
+                    let glyph = match (x == left || x == right, y == top || y == bottom) {
+                        (true, true) => '+',
+                        (false, true) => '-',
+                        (true, false) => '|',
+                        (false, false) => ' ',
+                    };
+                    if let Some(cell) = grid.cell(x, y) {
+                        *cell = Cell { glyph, ..BLANK };
+                    }
+                }
+            }
+            if let Some(title) = title {
+                let title = format!(" {} ", title);
+                self.print(layer, left + (size.0 - title.chars().count() as i32) / 2, top, &title, WHITE);
+            }
+        }
+
+        fn compose(&mut self, layer: Layer, source: (i32, i32), size: (i32, i32), destination: (i32, i32), opacity: f32) {
+            let mut cells = Vec::new();
+            let grid = self.grid(layer);
+            for y in 0..size.1 {
+                for x in 0..size.0 {
+                    if let Some(cell) = grid.cell(source.0 + x, source.1 + y) {
+                        cells.push((destination.0 + x, destination.1 + y, *cell));
+                    }
+                }
+            }
+            let screen = self.grid(Layer::Screen);
+            for (x, y, from) in cells {
+                if let Some(cell) = screen.cell(x, y) {
+                    if opacity >= 1.0 {
+                        *cell = from;
+                    } else {
+                        cell.foreground = blend(cell.foreground, from.background, opacity);
+                        cell.background = blend(cell.background, from.background, opacity);
+                    }
+                }
+            }
+        }
+
+        fn present(&mut self) {}
+
+        fn set_fullscreen(&mut self, _fullscreen: bool) {}
+
+        fn is_closed(&self) -> bool {
+            return false;
+        }
+    }
+
+    /// Plays back a list of events, one per poll, on a clock that only moves when told to.
+    pub(crate) struct ScriptedInput {
+        pub events: VecDeque<Event>,
+        pub time: u128,
+    }
+
+    impl ScriptedInput {
+        pub fn new(events: &[Event]) -> ScriptedInput {
+            return ScriptedInput { events: events.iter().copied().collect(), time: 0 };
+        }
+    }
+
+    impl Input for ScriptedInput {
+        fn poll(&mut self) -> Option<Event> {
+            return self.events.pop_front();
+        }
+
+        fn elapsed_millis(&self) -> u128 {
+            return self.time;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn text_is_wrapped_between_words() {
+            let mut renderer = MemoryRenderer::new(12, 4);
+            let rows = renderer.print_wrapped(Layer::Screen, (1, 0), (10, 3), "The river has burst its banks", WHITE);
+            assert_eq!(rows, 3);
+            assert_eq!(renderer.snapshot(), [
+                " The river  ",
+                " has burst  ",
+                " its banks  ",
+                "            ",
+            ].join("\n"));
+        }
+
+        #[test]
+        fn frames_are_titled_and_clear_what_they_cover() {
+            let mut renderer = MemoryRenderer::new(12, 5);
+            renderer.print(Layer::Screen, 0, 2, "############", WHITE);
+            renderer.print_frame(Layer::Screen, (1, 0), (10, 5), Some("Menu"));
+            renderer.print_centred(Layer::Screen, 6, 2, "Quit", WHITE);
+            assert_eq!(renderer.snapshot(), [
+                " +- Menu -+ ",
+                " |        | ",
+                "#|  Quit  |#",
+                " |        | ",
+                " +--------+ ",
+            ].join("\n"));
+        }
+
+        #[test]
+        fn layers_are_composed_onto_the_screen() {
+            let mut renderer = MemoryRenderer::new(6, 3);
+            renderer.create_layer(Layer::Map, 10, 10);
+            renderer.clear(Layer::Map, BLACK);
+            renderer.put_glyph(Layer::Map, 4, 5, '@', WHITE);
+            renderer.put_glyph(Layer::Map, 9, 9, 'x', WHITE);
+            renderer.compose(Layer::Map, (2, 4), (6, 3), (0, 0), 1.0);
+            assert_eq!(renderer.snapshot(), [
+                "      ",
+                "  @   ",
+                "      ",
+            ].join("\n"));
+        }
+
+        #[test]
+        fn translucent_layers_tint_without_hiding_glyphs() {
+            let night = Color { r: 0, g: 0, b: 100 };
+            let mut renderer = MemoryRenderer::new(3, 1);
+            renderer.create_layer(Layer::Night, 3, 1);
+            renderer.clear(Layer::Night, night);
+            renderer.clear(Layer::Screen, Color { r: 200, g: 200, b: 0 });
+            renderer.put_glyph(Layer::Screen, 1, 0, '@', WHITE);
+            renderer.compose(Layer::Night, (0, 0), (3, 1), (0, 0), 0.5);
+            assert_eq!(renderer.snapshot(), " @ ");
+            let cell = renderer.cell(Layer::Screen, 1, 0);
+            assert_eq!(cell.background, Color { r: 100, g: 100, b: 50 });
+            assert_eq!(cell.foreground, Color { r: 128, g: 128, b: 178 });
+        }
+    }
+}
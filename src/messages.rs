@@ -0,0 +1,38 @@
+pub mod messages {
+    use crate::renderer::renderer::Color;
+
+    /// Starts the text with a capital letter, for messages that begin with a name or a word
+    /// used elsewhere in lower case.
+    pub fn capitalise(text: &str) -> String {
+        let mut letters = text.chars();
+        return match letters.next() {
+            Some(first) => first.to_uppercase().chain(letters).collect(),
+            None => String::new(),
+        };
+    }
+
+    /// The log of things that happened, newest last, shown on the right of the panel.
+    pub struct Messages {
+        messages: Vec<(String, Color)>,
+    }
+
+    impl Messages {
+        pub const MAX_MESSAGES: usize = 100;
+
+        pub fn new() -> Messages {
+            return Messages { messages: Vec::new() }
+        }
+
+        pub fn add<T: Into<String>>(&mut self, message: T, color: Color) {
+            self.messages.push((message.into(), color));
+            if self.messages.len() > Messages::MAX_MESSAGES {
+                self.messages.remove(0);
+            }
+        }
+
+        /// The messages from newest to oldest.
+        pub fn latest(&self) -> impl Iterator<Item = &(String, Color)> {
+            return self.messages.iter().rev();
+        }
+    }
+}
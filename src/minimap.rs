@@ -0,0 +1,170 @@
+pub mod minimap {
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::{Game, GameMap, House, Player, Position};
+    use crate::renderer::renderer::{Color, Layer, Renderer, WHITE};
+    use legion::IntoQuery;
+
+    /// A scaled down overview of the whole wrapped world, drawn in the top right corner of the
+    /// screen. Each minimap cell covers a block of tiles from the centre copy of the map, and
+    /// its colour is kept until a tile in the block changes.
+    pub struct Minimap {
+        pub visible: bool,
+        pub x: i32,
+        pub y: i32,
+        pub width: i32,
+        pub height: i32,
+        map_width: i32,
+        map_height: i32,
+        colors: Vec<Color>,
+        stale: Vec<bool>,
+        stale_cells: Vec<(i32, i32)>,
+    }
+
+    impl Minimap {
+        pub fn new(camera_width: i32, map_width: i32, map_height: i32) -> Minimap {
+            let width = (camera_width / 3).max(1);
+            let height = (width * map_height / map_width).max(1);
+            let size = (width * height) as usize;
+            return Minimap {
+                visible: false,
+                x: camera_width - width - 3,
+                y: PANEL_HEIGHT + 1,
+                width,
+                height,
+                map_width,
+                map_height,
+                colors: vec![COLOR_UNEXPLORED; size],
+                stale: vec![true; size],
+                stale_cells: (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).collect(),
+            }
+        }
+
+        pub fn toggle(&mut self) {
+            self.visible = !self.visible;
+        }
+
+        /// Size of the minimap console including its frame.
+        pub fn console_size(&self) -> (i32, i32) {
+            return (self.width + 2, self.height + 2);
+        }
+
+        pub fn contains(&self, screen_x: i32, screen_y: i32) -> bool {
+            let inside_x = screen_x > self.x && screen_x <= self.x + self.width;
+            let inside_y = screen_y > self.y && screen_y <= self.y + self.height;
+            return self.visible && inside_x && inside_y;
+        }
+
+        /// Converts a screen cell inside the minimap to a position in the centre copy of the map.
+        pub fn to_world(&self, screen_x: i32, screen_y: i32) -> Position {
+            let cell_x = screen_x - self.x - 1;
+            let cell_y = screen_y - self.y - 1;
+            let x = (cell_x * self.map_width + self.map_width / 2) / self.width;
+            let y = (cell_y * self.map_height + self.map_height / 2) / self.height;
+            return Position::new(x + self.map_width, y + self.map_height);
+        }
+
+        fn to_cell(&self, world_x: i32, world_y: i32) -> (i32, i32) {
+            let x = world_x.rem_euclid(self.map_width) * self.width / self.map_width;
+            let y = world_y.rem_euclid(self.map_height) * self.height / self.map_height;
+            return (x + 1, y + 1);
+        }
+
+        /// Marks the cell covering a tile, in any copy of the map, to be worked out again.
+        pub fn tile_changed(&mut self, x: i32, y: i32) {
+            let (cell_x, cell_y) = self.to_cell(x, y);
+            let (cell_x, cell_y) = (cell_x - 1, cell_y - 1);
+            let index = (cell_x + cell_y * self.width) as usize;
+            if !self.stale[index] {
+                self.stale[index] = true;
+                self.stale_cells.push((cell_x, cell_y));
+            }
+        }
+
+        /// Works out the colour of every cell a tile has changed in since the last refresh.
+        pub fn refresh(&mut self, map: &GameMap) {
+            for (cell_x, cell_y) in std::mem::take(&mut self.stale_cells) {
+                let left = self.map_width + cell_x * self.map_width / self.width;
+                let right = self.map_width + (cell_x + 1) * self.map_width / self.width;
+                let top = self.map_height + cell_y * self.map_height / self.height;
+                let bottom = self.map_height + (cell_y + 1) * self.map_height / self.height;
+
+                let explored = (top..bottom)
+                    .any(|y| (left..right).any(|x| map.is_explored(x as usize, y as usize)));
+                let tile = map.get_tile(((left + right) / 2) as usize, ((top + bottom) / 2) as usize);
+                let index = (cell_x + cell_y * self.width) as usize;
+                self.colors[index] = if explored { tile.display_color() } else { COLOR_UNEXPLORED };
+                self.stale[index] = false;
+            }
+        }
+
+        pub fn render(&self, renderer: &mut dyn Renderer, game: &Game) {
+            renderer.print_frame(Layer::Minimap, (0, 0), self.console_size(), Some("World"));
+
+            for cell_y in 0..self.height {
+                for cell_x in 0..self.width {
+                    let color = self.colors[(cell_x + cell_y * self.width) as usize];
+                    renderer.set_background(Layer::Minimap, cell_x + 1, cell_y + 1, color);
+                }
+            }
+
+            let mut house_query = <(&House, &Position)>::query();
+            for (_, position) in house_query.iter(&game.world) {
+                let (x, y) = self.to_cell(position.x, position.y);
+                renderer.put_glyph(Layer::Minimap, x, y, 'A', COLOR_MINIMAP_SETTLEMENT);
+            }
+
+            let mut player_query = <(&Player, &Position)>::query();
+            for (_, position) in player_query.iter(&game.world) {
+                let (x, y) = self.to_cell(position.x, position.y);
+                renderer.put_glyph(Layer::Minimap, x, y, '@', WHITE);
+            }
+
+            self.render_viewport(renderer, game);
+        }
+
+        fn render_viewport(&self, renderer: &mut dyn Renderer, game: &Game) {
+            let camera = &game.camera;
+            let (cell_left, cell_top) = self.to_cell(camera.left(), camera.top());
+            let cell_width = (camera.width * self.width / self.map_width).max(1);
+            let cell_height = (camera.height * self.height / self.map_height).max(1);
+
+            let wrap_x = |x: i32| (x - 1).rem_euclid(self.width) + 1;
+            let wrap_y = |y: i32| (y - 1).rem_euclid(self.height) + 1;
+            for x in cell_left..=cell_left + cell_width {
+                renderer.set_background(Layer::Minimap, wrap_x(x), wrap_y(cell_top), COLOR_MINIMAP_VIEWPORT);
+                renderer.set_background(Layer::Minimap, wrap_x(x), wrap_y(cell_top + cell_height), COLOR_MINIMAP_VIEWPORT);
+            }
+            for y in cell_top..=cell_top + cell_height {
+                renderer.set_background(Layer::Minimap, wrap_x(cell_left), wrap_y(y), COLOR_MINIMAP_VIEWPORT);
+                renderer.set_background(Layer::Minimap, wrap_x(cell_left + cell_width), wrap_y(y), COLOR_MINIMAP_VIEWPORT);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::game_objects::game_objects::Tile;
+
+        #[test]
+        fn cells_are_only_worked_out_again_once_a_tile_in_them_changes() {
+            // a 4x4 world on a 4x4 minimap, one tile to a cell
+            let mut map = GameMap::new(vec![vec![Tile::meadow(); 12]; 12]);
+            let mut minimap = Minimap::new(12, 4, 4);
+            minimap.refresh(&map);
+            assert!(minimap.colors.iter().all(|color| *color == COLOR_UNEXPLORED));
+
+            map.explore(5, 6);
+            minimap.refresh(&map);
+            let cell = (1 + 2 * minimap.width) as usize;
+            assert_eq!(minimap.colors[cell], COLOR_UNEXPLORED);
+
+            for (x, y) in map.take_changed_tiles() {
+                minimap.tile_changed(x, y);
+            }
+            minimap.refresh(&map);
+            assert_eq!(minimap.colors[cell], map.get_tile(5, 6).display_color());
+            assert_eq!(minimap.colors.iter().filter(|color| **color == COLOR_UNEXPLORED).count(), 15);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+pub mod names {
+    use rand::Rng;
+
+    const FIRST_NAME_STARTS: [&str; 16] = [
+        "Ai", "Bri", "Cia", "Dei", "Eo", "Fia", "Gra", "Lia",
+        "Mui", "Nia", "Oi", "Ro", "Sao", "Tad", "Ui", "Ca",
+    ];
+    const FIRST_NAME_ENDS: [&str; 12] = [
+        "bhe", "d", "ghan", "lin", "n", "nne", "ra", "rdan", "sin", "ne", "mh", "ch",
+    ];
+    const FAMILY_PREFIXES: [&str; 4] = ["Mac", "Ó ", "Nic", "Ní "];
+    const FAMILY_ROOTS: [&str; 12] = [
+        "Briain", "Ceallaigh", "Conaill", "Dálaigh", "Domhnaill", "Faoláin",
+        "Gráinne", "Murchú", "Néill", "Riain", "Súilleabháin", "Tuathail",
+    ];
+
+    /// Builds a villager name out of random syllables and a family name.
+    pub fn generate_name<R: Rng>(rng: &mut R) -> String {
+        let start = FIRST_NAME_STARTS[rng.gen_range(0, FIRST_NAME_STARTS.len())];
+        let end = FIRST_NAME_ENDS[rng.gen_range(0, FIRST_NAME_ENDS.len())];
+        let prefix = FAMILY_PREFIXES[rng.gen_range(0, FAMILY_PREFIXES.len())];
+        let root = FAMILY_ROOTS[rng.gen_range(0, FAMILY_ROOTS.len())];
+        return format!("{}{} {}{}", start, end, prefix, root);
+    }
+}
@@ -0,0 +1,118 @@
+pub mod options {
+    use crate::settings::settings::DEFAULT_SEED;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum MapSize {
+        Small,
+        Medium,
+        Large,
+    }
+
+    impl MapSize {
+        pub const ALL: [MapSize; 3] = [MapSize::Small, MapSize::Medium, MapSize::Large];
+
+        pub fn name(&self) -> &str {
+            return match self {
+                MapSize::Small => "Small",
+                MapSize::Medium => "Medium",
+                MapSize::Large => "Large",
+            };
+        }
+
+        /// Width and height of a single copy of the wrapped map.
+        pub fn dimensions(&self) -> (i32, i32) {
+            return match self {
+                MapSize::Small => (400, 180),
+                MapSize::Medium => (700, 315),
+                MapSize::Large => (1000, 450),
+            };
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Difficulty {
+        Easy,
+        Normal,
+        Hard,
+    }
+
+    impl Difficulty {
+        pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+        pub fn name(&self) -> &str {
+            return match self {
+                Difficulty::Easy => "Easy",
+                Difficulty::Normal => "Normal",
+                Difficulty::Hard => "Hard",
+            };
+        }
+
+        /// Percentage of the usual starting supplies the colony lands with.
+        pub fn supplies_percent(&self) -> i32 {
+            return match self {
+                Difficulty::Easy => 150,
+                Difficulty::Normal => 100,
+                Difficulty::Hard => 60,
+            };
+        }
+
+        /// Percentage of the usual number of raiders in each band.
+        pub fn raid_percent(&self) -> i32 {
+            return match self {
+                Difficulty::Easy => 50,
+                Difficulty::Normal => 100,
+                Difficulty::Hard => 150,
+            };
+        }
+
+        /// How the rival settlements feel about the player when the run starts.
+        pub fn starting_relation(&self) -> i32 {
+            return match self {
+                Difficulty::Easy => 20,
+                Difficulty::Normal => 0,
+                Difficulty::Hard => -30,
+            };
+        }
+    }
+
+    /// Everything picked on the new game screen.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct NewGame {
+        pub seed: u64,
+        pub map_size: MapSize,
+        pub difficulty: Difficulty,
+    }
+
+    impl NewGame {
+        pub fn new() -> NewGame {
+            return NewGame { seed: DEFAULT_SEED, map_size: MapSize::Large, difficulty: Difficulty::Normal }
+        }
+
+        /// The seed sampled by the terrain noise. Seeds are offset by a half so the default
+        /// seed generates the same world the game always used to.
+        pub fn noise_seed(&self) -> f64 {
+            return self.seed as f64 + 0.5;
+        }
+
+        /// Seed for one of the game's random number generators. The weather, wildlife,
+        /// settlements, raids, events, the simulation and the player's landing each have a
+        /// generator of their own, seeded from the world seed so the same seed always plays
+        /// out the same way. Each passes its own `stream` so none of them share a sequence.
+        pub fn rng_seed(&self, stream: u64) -> u64 {
+            return self.noise_seed().to_bits() + stream;
+        }
+    }
+
+    /// Settings that carry over from one run to the next.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Preferences {
+        pub fullscreen: bool,
+        pub edge_scrolling: bool,
+    }
+
+    impl Preferences {
+        pub fn new() -> Preferences {
+            return Preferences { fullscreen: false, edge_scrolling: true }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+pub mod raiders {
+    use crate::settings::settings::*;
+    use crate::ai::ai::step_towards;
+    use crate::combat::combat::{Defense, Health, arm, kill};
+    use crate::game_objects::game_objects::{Danger, Drawable, Faction, Game, House, Person, Player, Position, Watchtower, destroy_building};
+    use crate::stockpile::stockpile::Stockpile;
+    use crate::workshop::workshop::Workshop;
+    use crate::research::research::Library;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use legion::{Entity, World, IntoQuery};
+    use legion::storage::Component;
+
+    /// A member of a raiding band.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Raider {
+        pub days_raiding: i32,
+        pub time_since_last_move: u128,
+        /// How long the raider has been setting fire to the building next to them.
+        pub time_spent_burning: u128,
+    }
+
+    impl Raider {
+        pub fn new() -> Raider {
+            return Raider { days_raiding: 0, time_since_last_move: 0, time_spent_burning: 0 }
+        }
+    }
+
+    /// Trains the colony's militia and houses up to `MILITIA_PER_BARRACKS` of them.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Barracks {
+        pub militia: Vec<Entity>,
+    }
+
+    impl Barracks {
+        pub fn new() -> Barracks {
+            return Barracks { militia: Vec::new() }
+        }
+    }
+
+    /// Shoots at the nearest raider in range.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct GuardTower {
+        pub time_since_last_shot: u128,
+    }
+
+    impl GuardTower {
+        pub fn new() -> GuardTower {
+            return GuardTower { time_since_last_shot: 0 }
+        }
+    }
+
+    /// Schedules the raids on the colony.
+    pub struct Raids {
+        rng: StdRng,
+        days_until_next_raid: i32,
+        pub last_report: Option<String>,
+    }
+
+    impl Raids {
+        pub fn new(seed: u64) -> Raids {
+            return Raids { rng: StdRng::seed_from_u64(seed), days_until_next_raid: RAID_INTERVAL, last_report: None }
+        }
+    }
+
+    fn collect_buildings<T: Component>(world: &World, name: &'static str, buildings: &mut Vec<(Entity, Position, &'static str)>) {
+        let mut query = <(Entity, &T, &Position, &Faction)>::query();
+        for (entity, _, position, faction) in query.iter(world) {
+            if *faction == Faction::PLAYER {
+                buildings.push((*entity, *position, name));
+            }
+        }
+    }
+
+    /// Every building of the player's colony, with what to call it in the message log.
+    fn colony_buildings(world: &World) -> Vec<(Entity, Position, &'static str)> {
+        let mut buildings = Vec::new();
+        collect_buildings::<House>(world, "house", &mut buildings);
+        collect_buildings::<Stockpile>(world, "stockpile", &mut buildings);
+        collect_buildings::<Workshop>(world, "workshop", &mut buildings);
+        collect_buildings::<Watchtower>(world, "watchtower", &mut buildings);
+        collect_buildings::<Barracks>(world, "barracks", &mut buildings);
+        collect_buildings::<GuardTower>(world, "guard tower", &mut buildings);
+        collect_buildings::<Library>(world, "library", &mut buildings);
+        return buildings;
+    }
+
+    fn direction(from: Position, to: Position) -> &'static str {
+        let (x, y) = (to.x - from.x, to.y - from.y);
+        return if x.abs() > y.abs() * 2 {
+            if x > 0 { "east" } else { "west" }
+        } else if y.abs() > x.abs() * 2 {
+            if y > 0 { "south" } else { "north" }
+        } else {
+            match (x > 0, y > 0) {
+                (true, true) => "south-east",
+                (true, false) => "north-east",
+                (false, true) => "south-west",
+                (false, false) => "north-west",
+            }
+        };
+    }
+
+    pub(crate) fn describe_raids(game: &Game) -> String {
+        let mut query = <&Raider>::query();
+        let raiders = query.iter(&game.world).count();
+        return if raiders > 0 {
+            format!("Raiders: {} in the land", raiders)
+        } else {
+            format!("Raiders: next raid in {} days", game.raids.days_until_next_raid)
+        };
+    }
+
+    /// Sends raids at the colony every `RAID_INTERVAL` days, with a small chance of a surprise
+    /// raid on any other day once the colony has had time to settle. Bands grow with the
+    /// colony, and raiders who have been about for too long head home.
+    pub(crate) fn raid_system(game: &mut Game) {
+        let mut leaving = Vec::new();
+        let mut query = <(Entity, &mut Raider)>::query();
+        for (entity, raider) in query.iter_mut(&mut game.world) {
+            raider.days_raiding += 1;
+            if raider.days_raiding > RAIDER_MAX_DAYS {
+                leaving.push(*entity);
+            }
+        }
+        if !leaving.is_empty() {
+            game.raids.last_report = Some(String::from("The raiders have withdrawn"));
+        }
+        for raider in leaving {
+            game.world.remove(raider);
+        }
+
+        game.raids.days_until_next_raid -= 1;
+        if game.calendar.days_elapsed < RAID_GRACE_DAYS {
+            return;
+        }
+        let scheduled = game.raids.days_until_next_raid <= 0;
+        if !scheduled && game.raids.rng.gen_range(0, 100) >= RAID_CHANCE {
+            return;
+        }
+        if scheduled {
+            game.raids.days_until_next_raid = RAID_INTERVAL;
+        }
+        let size = (RAID_BASE_SIZE + game.population / RAID_POPULATION_PER_RAIDER) * game.difficulty.raid_percent() / 100;
+        spawn_band(game, size.max(1));
+    }
+
+    /// Looks for somewhere out of the colony's sight for a band to gather, in a random
+    /// direction from the building they are after.
+    fn find_camp(game: &mut Game, target: Position) -> Option<Position> {
+        for _ in 0..50 {
+            let angle = game.raids.rng.gen_range(0.0, std::f64::consts::PI * 2.0);
+            for reach in RAID_MIN_DISTANCE..=RAID_MAX_DISTANCE {
+                let x = target.x + (angle.cos() * reach as f64) as i32;
+                let y = target.y + (angle.sin() * reach as f64) as i32;
+                if !game.map.is_explored(x as usize, y as usize) && !game.map.is_tile_blocked(x, y) {
+                    return Some(Position::new(x, y));
+                }
+            }
+        }
+        return None;
+    }
+
+    pub(crate) fn spawn_band(game: &mut Game, size: i32) {
+        let buildings = colony_buildings(&game.world);
+        if buildings.is_empty() {
+            return;
+        }
+        let target = buildings[game.raids.rng.gen_range(0, buildings.len())].1;
+        let camp = match find_camp(game, target) {
+            Some(camp) => camp,
+            None => return,
+        };
+
+        let mut spawned = 0;
+        for _ in 0..size * 5 {
+            let x = camp.x + game.raids.rng.gen_range(-2, 3);
+            let y = camp.y + game.raids.rng.gen_range(-2, 3);
+            if game.map.is_tile_blocked(x, y) {
+                continue;
+            }
+            let raider = game.world.push((Position::new(x, y), Drawable::new('R', COLOR_RAIDER), Raider::new(), Danger));
+            arm(&mut game.world, raider, RAIDER_HEALTH, RAIDER_ATTACK, RAIDER_ARMOUR);
+            spawned += 1;
+            if spawned == size {
+                break;
+            }
+        }
+        let report = format!("A band of {} raiders approaches from the {}", spawned, direction(target, camp));
+        game.messages.add(report.clone(), COLOR_RAIDER);
+        game.raids.last_report = Some(report);
+    }
+
+    /// Raiders march on the nearest building of the colony and burn it down, chasing anyone
+    /// of the colony who comes close on the way. The fighting itself is left to the combat
+    /// system.
+    pub(crate) fn raider_system(game: &mut Game, time_delta: u128) {
+        let buildings = colony_buildings(&game.world);
+        let mut people: Vec<Position> = Vec::new();
+        let mut player_query = <(&Player, &Position)>::query();
+        for (player, position) in player_query.iter(&game.world) {
+            if player.alive {
+                people.push(*position);
+            }
+        }
+        let mut person_query = <(&Person, &Position, &Faction)>::query();
+        for (_, position, faction) in person_query.iter(&game.world) {
+            if *faction == Faction::PLAYER {
+                people.push(*position);
+            }
+        }
+
+        let mut burnt: Vec<(Entity, &'static str)> = Vec::new();
+        let mut query = <(&mut Raider, &mut Position)>::query();
+        for (raider, position) in query.iter_mut(&mut game.world) {
+            raider.time_since_last_move += time_delta;
+            if raider.time_since_last_move < RAIDER_MOVE_TIME {
+                continue;
+            }
+            raider.time_since_last_move = 0;
+
+            let here = *position;
+            let victim = people.iter()
+                .filter(|person| here.distance(**person) <= RAIDER_CHASE_RADIUS)
+                .min_by_key(|person| here.distance(**person));
+            if let Some(victim) = victim {
+                raider.time_spent_burning = 0;
+                if here.distance(*victim) > 1 {
+                    step_towards(&game.map, position, *victim);
+                }
+                continue;
+            }
+
+            let building = buildings.iter()
+                .filter(|(entity, _, _)| !burnt.iter().any(|(gone, _)| gone == entity))
+                .min_by_key(|(_, building, _)| here.distance(*building));
+            match building {
+                Some((entity, building, name)) if here.distance(*building) <= 1 => {
+                    raider.time_spent_burning += RAIDER_MOVE_TIME;
+                    if raider.time_spent_burning >= RAIDER_BURN_TIME {
+                        raider.time_spent_burning = 0;
+                        burnt.push((*entity, *name));
+                    }
+                }
+                Some((_, building, _)) => step_towards(&game.map, position, *building),
+                None => {}
+            }
+        }
+
+        for (building, name) in burnt {
+            destroy_building(&mut game.world, &mut game.map, building);
+            game.messages.add(format!("Raiders have burnt down a {}", name), COLOR_DEATH);
+        }
+    }
+
+    /// Guard towers shoot the nearest raider in range whenever they are ready to fire.
+    pub(crate) fn guard_tower_system(game: &mut Game, time_delta: u128) {
+        let mut raider_query = <(Entity, &Raider, &Position, Option<&Defense>)>::query();
+        let raiders: Vec<(Entity, Position, i32)> = raider_query.iter(&game.world)
+            .map(|(entity, _, position, defense)| (*entity, *position, defense.map_or(0, |defense| defense.armour)))
+            .collect();
+
+        let mut shots = Vec::new();
+        let mut query = <(&mut GuardTower, &Position)>::query();
+        for (tower, position) in query.iter_mut(&mut game.world) {
+            tower.time_since_last_shot += time_delta;
+            if tower.time_since_last_shot < GUARD_TOWER_FIRE_TIME {
+                continue;
+            }
+            let target = raiders.iter()
+                .filter(|(_, raider, _)| position.distance(*raider) <= GUARD_TOWER_RANGE)
+                .min_by_key(|(_, raider, _)| position.distance(*raider));
+            if let Some((raider, _, armour)) = target {
+                tower.time_since_last_shot = 0;
+                shots.push((*raider, (GUARD_TOWER_DAMAGE - armour).max(1)));
+            }
+        }
+
+        for (raider, damage) in shots {
+            let mut query = <&mut Health>::query();
+            if let Ok(health) = query.get_mut(&mut game.world, raider) {
+                if health.current <= 0 {
+                    continue;
+                }
+                health.current -= damage;
+                if health.current <= 0 {
+                    kill(&mut game.world, raider);
+                    game.messages.add(String::from("A guard tower has shot down a raider"), COLOR_COMBAT);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+pub mod recipes {
+    use std::fs;
+    use serde::Deserialize;
+    use crate::resources::resources::{ResourceId, ResourceRegistry};
+
+    pub const RECIPES_PATH: &str = "data/recipes.ron";
+
+    #[derive(Deserialize)]
+    struct RecipeDefinition {
+        name: String,
+        inputs: Vec<(String, i32)>,
+        outputs: Vec<(String, i32)>,
+        time: u128,
+    }
+
+    #[derive(Deserialize)]
+    struct BuildingDefinition {
+        name: String,
+        glyph: char,
+        cost: Vec<(String, i32)>,
+        workers: usize,
+        recipes: Vec<RecipeDefinition>,
+    }
+
+    #[derive(Deserialize)]
+    struct RecipeFile {
+        buildings: Vec<BuildingDefinition>,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Recipe {
+        pub name: String,
+        pub inputs: Vec<(ResourceId, i32)>,
+        pub outputs: Vec<(ResourceId, i32)>,
+        pub time: u128,
+    }
+
+    /// A kind of production building with its resource names resolved to ids.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct WorkshopKind {
+        pub name: String,
+        pub glyph: char,
+        pub cost: Vec<(ResourceId, i32)>,
+        pub workers: usize,
+        pub recipes: Vec<Recipe>,
+    }
+
+    /// Every production building and recipe, loaded from `RECIPES_PATH`.
+    pub struct RecipeBook {
+        pub workshops: Vec<WorkshopKind>,
+    }
+
+    impl RecipeBook {
+        pub fn load(resources: &ResourceRegistry) -> RecipeBook {
+            let text = fs::read_to_string(RECIPES_PATH)
+                .unwrap_or_else(|error| panic!("Could not read {}: {}", RECIPES_PATH, error));
+            return RecipeBook::parse(&text, resources);
+        }
+
+        pub fn parse(text: &str, resources: &ResourceRegistry) -> RecipeBook {
+            let file: RecipeFile = ron::de::from_str(text)
+                .unwrap_or_else(|error| panic!("Could not parse {}: {}", RECIPES_PATH, error));
+            let resolve = |amounts: &[(String, i32)]| -> Vec<(ResourceId, i32)> {
+                amounts.iter().map(|(id, amount)| (resources.id(id), *amount)).collect()
+            };
+            let workshops = file.buildings.iter()
+                .map(|building| WorkshopKind {
+                    name: building.name.clone(),
+                    glyph: building.glyph,
+                    cost: resolve(&building.cost),
+                    workers: building.workers,
+                    recipes: building.recipes.iter()
+                        .map(|recipe| {
+                            // progress is shown as a share of the time, and a run has to take some work
+                            if recipe.time == 0 {
+                                panic!("Recipe {} in {} takes no time", recipe.name, RECIPES_PATH);
+                            }
+                            Recipe {
+                                name: recipe.name.clone(),
+                                inputs: resolve(&recipe.inputs),
+                                outputs: resolve(&recipe.outputs),
+                                time: recipe.time,
+                            }
+                        })
+                        .collect(),
+                })
+                .collect();
+            return RecipeBook { workshops };
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn resources() -> ResourceRegistry {
+            return ResourceRegistry::parse(r#"(
+                resources: [
+                    (id: "wood", name: "Wood", method: Gather, sources: [Forest]),
+                    (id: "planks", name: "Planks", method: Craft),
+                ],
+            )"#);
+        }
+
+        fn sawmill(recipe: &str) -> String {
+            return format!(r#"(
+                buildings: [
+                    (name: "Sawmill", glyph: 'S', cost: [("wood", 20)], workers: 2, recipes: [{}]),
+                ],
+            )"#, recipe);
+        }
+
+        #[test]
+        fn recipe_file_is_resolved_against_the_resources() {
+            let resources = ResourceRegistry::load();
+            let recipes = RecipeBook::load(&resources);
+            let sawmill = recipes.workshops.iter().find(|kind| kind.name == "Sawmill").unwrap();
+            assert_eq!(sawmill.recipes[0].inputs, vec![(resources.id("wood"), 2)]);
+            assert_eq!(sawmill.recipes[0].outputs, vec![(resources.id("planks"), 1)]);
+            assert!(recipes.workshops.iter().flat_map(|kind| kind.recipes.iter()).all(|recipe| recipe.time > 0));
+        }
+
+        #[test]
+        fn recipes_are_parsed() {
+            let text = sawmill(r#"(name: "Planks", inputs: [("wood", 2)], outputs: [("planks", 1)], time: 3000)"#);
+            let recipes = RecipeBook::parse(&text, &resources());
+            assert_eq!(recipes.workshops[0].cost, vec![(ResourceId(0), 20)]);
+            assert_eq!(recipes.workshops[0].recipes[0], Recipe {
+                name: String::from("Planks"),
+                inputs: vec![(ResourceId(0), 2)],
+                outputs: vec![(ResourceId(1), 1)],
+                time: 3000,
+            });
+        }
+
+        #[test]
+        #[should_panic(expected = "takes no time")]
+        fn recipes_that_take_no_time_are_rejected() {
+            let text = sawmill(r#"(name: "Planks", inputs: [("wood", 2)], outputs: [("planks", 1)], time: 0)"#);
+            RecipeBook::parse(&text, &resources());
+        }
+
+        #[test]
+        #[should_panic(expected = "Unknown resource 'nails'")]
+        fn unknown_resources_are_rejected() {
+            let text = sawmill(r#"(name: "Planks", inputs: [("wood", 2)], outputs: [("nails", 1)], time: 3000)"#);
+            RecipeBook::parse(&text, &resources());
+        }
+    }
+}
@@ -0,0 +1,144 @@
+pub mod render_cache {
+    use std::collections::HashMap;
+    use crate::game_objects::game_objects::Position;
+    use crate::camera::camera::Camera;
+    use legion::Entity;
+
+    /// Tracks which cells of the wrapped `con` console are out of date, so `render_all` only
+    /// redraws cells whose tile, visibility or entities changed since the last frame.
+    pub struct RenderCache {
+        width: i32,
+        height: i32,
+        dirty: Vec<bool>,
+        dirty_cells: Vec<(i32, i32)>,
+        drawn: Vec<bool>,
+        visible_cells: Vec<(i32, i32)>,
+        entity_positions: HashMap<Entity, Position>,
+        last_camera: Option<(i32, i32)>,
+    }
+
+    impl RenderCache {
+        pub fn new(width: i32, height: i32) -> RenderCache {
+            let size = (width * height) as usize;
+            return RenderCache {
+                width,
+                height,
+                dirty: vec![false; size],
+                dirty_cells: Vec::new(),
+                drawn: vec![false; size],
+                visible_cells: Vec::new(),
+                entity_positions: HashMap::new(),
+                last_camera: None,
+            }
+        }
+
+        fn index(&self, x: i32, y: i32) -> Option<usize> {
+            if x < 0 || y < 0 || x >= self.width || y >= self.height {
+                return None;
+            }
+            return Some((x + y * self.width) as usize);
+        }
+
+        pub fn mark_dirty(&mut self, x: i32, y: i32) {
+            if let Some(index) = self.index(x, y) {
+                if !self.dirty[index] {
+                    self.dirty[index] = true;
+                    self.dirty_cells.push((x, y));
+                }
+            }
+        }
+
+        /// Marks the cell in every copy of the wrapped map.
+        pub fn mark_dirty_wrapped(&mut self, x: i32, y: i32) {
+            let (map_width, map_height) = (self.width / 3, self.height / 3);
+            let (x, y) = (x.rem_euclid(map_width), y.rem_euclid(map_height));
+            for i in 0..3 {
+                for j in 0..3 {
+                    self.mark_dirty(x + map_width * i, y + map_height * j);
+                }
+            }
+        }
+
+        pub fn is_dirty(&self, x: i32, y: i32) -> bool {
+            return match self.index(x, y) {
+                Some(index) => self.dirty[index],
+                None => false,
+            };
+        }
+
+        /// Replaces the cells lit by the last FOV computation, dirtying both the cells that
+        /// went dark and the cells that became visible.
+        pub fn set_visible_cells(&mut self, visible_cells: Vec<(i32, i32)>) {
+            let previous = std::mem::replace(&mut self.visible_cells, visible_cells);
+            for (x, y) in previous {
+                self.mark_dirty_wrapped(x, y);
+            }
+            for i in 0..self.visible_cells.len() {
+                let (x, y) = self.visible_cells[i];
+                self.mark_dirty_wrapped(x, y);
+            }
+        }
+
+        /// Dirties the old and new cells of every drawable entity that moved, appeared or
+        /// disappeared since the previous frame.
+        pub fn track_entities(&mut self, positions: HashMap<Entity, Position>) {
+            let previous = std::mem::replace(&mut self.entity_positions, positions);
+            for (entity, old_position) in previous.iter() {
+                match self.entity_positions.get(entity).copied() {
+                    Some(position) if position == *old_position => {}
+                    Some(position) => {
+                        self.mark_dirty(old_position.x, old_position.y);
+                        self.mark_dirty(position.x, position.y);
+                    }
+                    None => self.mark_dirty(old_position.x, old_position.y),
+                }
+            }
+            let appeared: Vec<Position> = self.entity_positions.iter()
+                .filter(|(entity, _)| !previous.contains_key(entity))
+                .map(|(_, position)| *position)
+                .collect();
+            for position in appeared {
+                self.mark_dirty(position.x, position.y);
+            }
+        }
+
+        /// Returns the dirty cells inside the camera's view that must be redrawn this frame.
+        /// Dirty cells outside the view are remembered as stale and redrawn once scrolled to.
+        pub fn begin_frame(&mut self, camera: &Camera) -> Vec<(i32, i32)> {
+            if self.last_camera != Some((camera.x, camera.y)) {
+                self.last_camera = Some((camera.x, camera.y));
+                for y in camera.top()..camera.bottom() {
+                    for x in camera.left()..camera.right() {
+                        if let Some(index) = self.index(x, y) {
+                            if !self.drawn[index] {
+                                self.mark_dirty(x, y);
+                            }
+                        }
+                    }
+                }
+            }
+
+            let mut redraw = Vec::new();
+            for i in 0..self.dirty_cells.len() {
+                let (x, y) = self.dirty_cells[i];
+                let index = (x + y * self.width) as usize;
+                let in_view = x >= camera.left() && x < camera.right() && y >= camera.top() && y < camera.bottom();
+                if in_view {
+                    self.drawn[index] = true;
+                    redraw.push((x, y));
+                } else {
+                    self.drawn[index] = false;
+                    self.dirty[index] = false;
+                }
+            }
+            self.dirty_cells = redraw.clone();
+            return redraw;
+        }
+
+        pub fn end_frame(&mut self) {
+            for (x, y) in self.dirty_cells.drain(..) {
+                self.dirty[(x + y * self.width) as usize] = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+pub mod renderer {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Color {
+        pub r: u8,
+        pub g: u8,
+        pub b: u8,
+    }
+
+    pub const WHITE: Color = Color { r: 255, g: 255, b: 255 };
+    pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+
+    /// The surfaces the game draws on. Everything but the screen is drawn offscreen and then
+    /// composed onto the screen.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum Layer {
+        Screen,
+        /// The whole wrapped map, of which the camera's view is composed each frame.
+        Map,
+        Night,
+        Minimap,
+        Panel,
+        Inspect,
+        Popup,
+    }
+
+    /// Everything the game needs from a display: cells on layers holding a glyph with a
+    /// foreground and background colour. Positions and sizes are in cells, and anything drawn
+    /// outside a layer is dropped.
+    pub(crate) trait Renderer {
+        fn screen_size(&self) -> (i32, i32);
+        /// Creates a layer, replacing any earlier layer of the same kind.
+        fn create_layer(&mut self, layer: Layer, width: i32, height: i32);
+        fn clear(&mut self, layer: Layer, background: Color);
+        fn put_glyph(&mut self, layer: Layer, x: i32, y: i32, glyph: char, color: Color);
+        fn set_background(&mut self, layer: Layer, x: i32, y: i32, color: Color);
+        fn print(&mut self, layer: Layer, x: i32, y: i32, text: &str, color: Color);
+        /// Prints a line of text centred on `x`.
+        fn print_centred(&mut self, layer: Layer, x: i32, y: i32, text: &str, color: Color);
+        /// Prints text wrapped to fit the area, returning the number of rows it took.
+        fn print_wrapped(&mut self, layer: Layer, position: (i32, i32), size: (i32, i32), text: &str, color: Color) -> i32;
+        /// Clears an area to black and draws a white frame around it, with the title on top.
+        fn print_frame(&mut self, layer: Layer, position: (i32, i32), size: (i32, i32), title: Option<&str>);
+        /// Copies part of a layer onto the screen. Below full opacity the layer's colours are
+        /// blended over what is already there.
+        fn compose(&mut self, layer: Layer, source: (i32, i32), size: (i32, i32), destination: (i32, i32), opacity: f32);
+        /// Shows the screen as drawn so far.
+        fn present(&mut self);
+        fn set_fullscreen(&mut self, fullscreen: bool);
+        fn is_closed(&self) -> bool;
+    }
+}
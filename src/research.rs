@@ -0,0 +1,393 @@
+pub mod research {
+    use std::collections::{HashMap, HashSet};
+    use std::fs;
+    use serde::Deserialize;
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::{Faction, Game, Job};
+    use crate::recipes::recipes::RecipeBook;
+    use crate::resources::resources::Inventory;
+    use crate::staffing::staffing::{jobs, staff};
+    use legion::{Entity, IntoQuery};
+
+    pub const RESEARCH_PATH: &str = "data/research.ron";
+
+    /// Buildings the player raises with a key of their own rather than from the recipe book.
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+    pub(crate) enum Building {
+        Watchtower,
+        Stockpile,
+        Barracks,
+        GuardTower,
+        Library,
+    }
+
+    impl Building {
+        pub fn name(&self) -> &'static str {
+            return match self {
+                Building::Watchtower => "watchtower",
+                Building::Stockpile => "stockpile",
+                Building::Barracks => "barracks",
+                Building::GuardTower => "guard tower",
+                Building::Library => "library",
+            };
+        }
+    }
+
+    /// Changes villagers can make to the land itself.
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+    pub(crate) enum Improvement {
+        ClearForest,
+        Road,
+        Irrigation,
+        Tunnel,
+    }
+
+    impl Improvement {
+        pub fn name(&self) -> &'static str {
+            return match self {
+                Improvement::ClearForest => "clearing forest",
+                Improvement::Road => "roads",
+                Improvement::Irrigation => "irrigation",
+                Improvement::Tunnel => "tunnelling",
+            };
+        }
+    }
+
+    /// Lasting improvements to the colony's work, each a percentage on top of the usual rate.
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+    pub(crate) enum Bonus {
+        Farming,
+        Gathering,
+        Crafting,
+        Study,
+    }
+
+    impl Bonus {
+        pub fn name(&self) -> &'static str {
+            return match self {
+                Bonus::Farming => "farming",
+                Bonus::Gathering => "gathering",
+                Bonus::Crafting => "crafting",
+                Bonus::Study => "study",
+            };
+        }
+    }
+
+    #[derive(Deserialize)]
+    enum UnlockDefinition {
+        Building(Building),
+        Workshop(String),
+        Recipe(String, String),
+        Improvement(Improvement),
+        Bonus(Bonus, i32),
+    }
+
+    #[derive(Deserialize)]
+    struct TechnologyDefinition {
+        name: String,
+        cost: i32,
+        requires: Vec<String>,
+        unlocks: Vec<UnlockDefinition>,
+    }
+
+    #[derive(Deserialize)]
+    struct ResearchFile {
+        technologies: Vec<TechnologyDefinition>,
+    }
+
+    /// Something a technology makes available, with workshops and recipes referred to by
+    /// their place in the recipe book.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Unlock {
+        Building(Building),
+        Workshop(usize),
+        Recipe(usize, usize),
+        Improvement(Improvement),
+        Bonus(Bonus, i32),
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Technology {
+        pub name: String,
+        pub cost: i32,
+        pub requires: Vec<usize>,
+        pub unlocks: Vec<Unlock>,
+    }
+
+    /// A building where scholars study whatever the colony is researching.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Library {
+        pub scholars: Vec<Entity>,
+    }
+
+    impl Library {
+        pub fn new() -> Library {
+            return Library { scholars: Vec::new() }
+        }
+    }
+
+    /// The technology tree, loaded from `RESEARCH_PATH`, with what the colony has learned so
+    /// far and what it is studying now.
+    pub struct Research {
+        pub tree: Vec<Technology>,
+        pub known: Vec<bool>,
+        pub current: Option<usize>,
+        pub points: i32,
+    }
+
+    impl Research {
+        pub fn load(recipes: &RecipeBook) -> Research {
+            let text = fs::read_to_string(RESEARCH_PATH)
+                .unwrap_or_else(|error| panic!("Could not read {}: {}", RESEARCH_PATH, error));
+            return Research::parse(&text, recipes);
+        }
+
+        pub fn parse(text: &str, recipes: &RecipeBook) -> Research {
+            let file: ResearchFile = ron::de::from_str(text)
+                .unwrap_or_else(|error| panic!("Could not parse {}: {}", RESEARCH_PATH, error));
+            let workshop = |name: &str| -> usize {
+                recipes.workshops.iter()
+                    .position(|kind| kind.name == name)
+                    .unwrap_or_else(|| panic!("Unknown workshop {} in {}", name, RESEARCH_PATH))
+            };
+            let technology = |name: &str| -> usize {
+                file.technologies.iter()
+                    .position(|technology| technology.name == name)
+                    .unwrap_or_else(|| panic!("Unknown technology {} in {}", name, RESEARCH_PATH))
+            };
+            let tree: Vec<Technology> = file.technologies.iter()
+                .map(|definition| Technology {
+                    name: definition.name.clone(),
+                    cost: definition.cost,
+                    requires: definition.requires.iter().map(|name| technology(name)).collect(),
+                    unlocks: definition.unlocks.iter()
+                        .map(|unlock| match unlock {
+                            UnlockDefinition::Building(building) => Unlock::Building(*building),
+                            UnlockDefinition::Workshop(name) => Unlock::Workshop(workshop(name)),
+                            UnlockDefinition::Recipe(building, name) => {
+                                let kind = workshop(building);
+                                let recipe = recipes.workshops[kind].recipes.iter()
+                                    .position(|recipe| recipe.name == *name)
+                                    .unwrap_or_else(|| panic!("Unknown recipe {} in {}", name, RESEARCH_PATH));
+                                Unlock::Recipe(kind, recipe)
+                            }
+                            UnlockDefinition::Improvement(improvement) => Unlock::Improvement(*improvement),
+                            UnlockDefinition::Bonus(bonus, percent) => Unlock::Bonus(*bonus, *percent),
+                        })
+                        .collect(),
+                })
+                .collect();
+            let known = vec![false; tree.len()];
+            return Research { tree, known, current: None, points: 0 };
+        }
+
+        /// Whether the colony can make use of something. Anything no technology unlocks is
+        /// known from the start.
+        pub fn is_unlocked(&self, unlock: Unlock) -> bool {
+            let unlocked_by: Vec<usize> = (0..self.tree.len())
+                .filter(|index| self.tree[*index].unlocks.contains(&unlock))
+                .collect();
+            return unlocked_by.is_empty() || unlocked_by.iter().any(|index| self.known[*index]);
+        }
+
+        /// The total percentage added by every known technology granting the bonus.
+        pub fn bonus(&self, bonus: Bonus) -> i32 {
+            return (0..self.tree.len())
+                .filter(|index| self.known[*index])
+                .flat_map(|index| self.tree[index].unlocks.iter())
+                .map(|unlock| match unlock {
+                    Unlock::Bonus(kind, percent) if *kind == bonus => *percent,
+                    _ => 0,
+                })
+                .sum();
+        }
+
+        /// Technologies not yet known whose requirements all are.
+        pub fn available(&self) -> Vec<usize> {
+            return (0..self.tree.len())
+                .filter(|index| !self.known[*index])
+                .filter(|index| self.tree[*index].requires.iter().all(|required| self.known[*required]))
+                .collect();
+        }
+
+        /// Switches study to another technology. Points put towards the old one are lost.
+        pub fn select(&mut self, technology: usize) {
+            if self.current != Some(technology) {
+                self.current = Some(technology);
+                self.points = 0;
+            }
+        }
+
+        /// Puts points towards the current technology, returning it if they were enough to
+        /// learn it.
+        pub fn add_points(&mut self, points: i32) -> Option<usize> {
+            let index = match self.current {
+                Some(index) if points > 0 => index,
+                _ => return None,
+            };
+            self.points += points;
+            if self.points < self.tree[index].cost {
+                return None;
+            }
+            self.known[index] = true;
+            self.current = None;
+            self.points = 0;
+            return Some(index);
+        }
+
+        pub fn describe(&self) -> String {
+            return match self.current {
+                Some(index) => format!("Research: {} {}/{}", self.tree[index].name, self.points, self.tree[index].cost),
+                None => String::from("Research: nothing (u to choose)"),
+            };
+        }
+
+        pub fn describe_technology(&self, index: usize, recipes: &RecipeBook) -> String {
+            let technology = &self.tree[index];
+            let unlocks: Vec<String> = technology.unlocks.iter()
+                .map(|unlock| match unlock {
+                    Unlock::Building(building) => building.name().to_string(),
+                    Unlock::Workshop(kind) => recipes.workshops[*kind].name.to_lowercase(),
+                    Unlock::Recipe(kind, recipe) => recipes.workshops[*kind].recipes[*recipe].name.to_lowercase(),
+                    Unlock::Improvement(improvement) => improvement.name().to_string(),
+                    Unlock::Bonus(bonus, percent) => format!("{:+}% {}", percent, bonus.name()),
+                })
+                .collect();
+            return format!("{} ({} points): {}", technology.name, technology.cost, unlocks.join(", "));
+        }
+    }
+
+    /// Puts the scholars' work towards the current technology, announcing it once learned.
+    pub(crate) fn study(game: &mut Game, points: i32) {
+        if let Some(index) = game.research.add_points(points) {
+            let name = game.research.tree[index].name.clone();
+            game.messages.add(format!("Our scholars have mastered {}", name), COLOR_RESEARCH);
+        }
+    }
+
+    /// Fills the colony's libraries with scholars while there is something to research.
+    pub(crate) fn staff_libraries(game: &mut Game, stocks: &HashMap<Faction, Inventory>) {
+        let jobs = jobs(&game.world);
+        let researching = game.research.current.is_some();
+        let mut standing = HashSet::new();
+        let mut vacancies = Vec::new();
+        let mut library_query = <(Entity, &mut Library)>::query();
+        for (entity, library) in library_query.iter_mut(&mut game.world) {
+            library.scholars.retain(|scholar| jobs.get(scholar) == Some(&Some(Job::Scholar(*entity))));
+            standing.insert(*entity);
+            if researching {
+                vacancies.extend(std::iter::repeat_n((*entity, Faction::PLAYER), SCHOLARS_PER_LIBRARY - library.scholars.len()));
+            }
+        }
+
+        let staffing = staff(game, stocks, &standing, vacancies, Job::Scholar);
+        let mut library_query = <&mut Library>::query();
+        for (library, scholar) in staffing.hired {
+            if let Ok(library) = library_query.get_mut(&mut game.world, library) {
+                library.scholars.push(scholar);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::resources::resources::ResourceRegistry;
+
+        fn recipes() -> RecipeBook {
+            let resources = ResourceRegistry::parse(r#"(
+                resources: [
+                    (id: "wood", name: "Wood", method: Gather, sources: [Forest]),
+                    (id: "planks", name: "Planks", method: Craft),
+                ],
+            )"#);
+            return RecipeBook::parse(r#"(
+                buildings: [
+                    (name: "Sawmill", glyph: 'S', cost: [("wood", 20)], workers: 2, recipes: [
+                        (name: "Planks", inputs: [("wood", 2)], outputs: [("planks", 1)], time: 3000),
+                        (name: "Beams", inputs: [("wood", 4)], outputs: [("planks", 3)], time: 5000),
+                    ]),
+                ],
+            )"#, &resources);
+        }
+
+        /// Woodcraft, then Surveying once it is known.
+        fn research() -> Research {
+            return Research::parse(r#"(
+                technologies: [
+                    (name: "Woodcraft", cost: 100, requires: [], unlocks: [Workshop("Sawmill"), Bonus(Study, 25)]),
+                    (name: "Surveying", cost: 50, requires: ["Woodcraft"], unlocks: [Building(Watchtower), Recipe("Sawmill", "Beams")]),
+                ],
+            )"#, &recipes());
+        }
+
+        #[test]
+        fn points_build_up_until_the_technology_is_learned() {
+            let mut research = research();
+            research.select(0);
+            assert_eq!(research.add_points(60), None);
+            assert_eq!(research.points, 60);
+            assert!(!research.is_unlocked(Unlock::Workshop(0)));
+
+            assert_eq!(research.add_points(50), Some(0));
+            assert!(research.known[0]);
+            assert_eq!((research.current, research.points), (None, 0));
+            assert!(research.is_unlocked(Unlock::Workshop(0)));
+            assert_eq!(research.bonus(Bonus::Study), 25);
+        }
+
+        #[test]
+        fn points_go_nowhere_without_a_technology_chosen() {
+            let mut research = research();
+            assert_eq!(research.add_points(500), None);
+            assert_eq!(research.points, 0);
+            assert!(research.known.iter().all(|known| !known));
+        }
+
+        #[test]
+        fn switching_technology_loses_the_points() {
+            let mut research = research();
+            research.select(0);
+            research.add_points(60);
+            research.select(0);
+            assert_eq!(research.points, 60);
+            research.select(1);
+            assert_eq!(research.points, 0);
+        }
+
+        #[test]
+        fn technologies_wait_for_their_requirements() {
+            let mut research = research();
+            assert_eq!(research.available(), vec![0]);
+            research.select(0);
+            research.add_points(100);
+            assert_eq!(research.available(), vec![1]);
+        }
+
+        #[test]
+        fn buildings_and_recipes_wait_for_their_technology() {
+            let mut research = research();
+            // the first recipe and anything no technology unlocks are there from the start
+            assert!(research.is_unlocked(Unlock::Recipe(0, 0)));
+            assert!(research.is_unlocked(Unlock::Building(Building::Library)));
+            assert!(!research.is_unlocked(Unlock::Building(Building::Watchtower)));
+            assert!(!research.is_unlocked(Unlock::Recipe(0, 1)));
+
+            research.known[0] = true;
+            research.select(1);
+            research.add_points(50);
+            assert!(research.is_unlocked(Unlock::Building(Building::Watchtower)));
+            assert!(research.is_unlocked(Unlock::Recipe(0, 1)));
+        }
+
+        #[test]
+        #[should_panic(expected = "Unknown technology Alchemy")]
+        fn unknown_requirements_are_rejected() {
+            Research::parse(r#"(
+                technologies: [
+                    (name: "Chemistry", cost: 100, requires: ["Alchemy"], unlocks: []),
+                ],
+            )"#, &recipes());
+        }
+    }
+}
@@ -0,0 +1,158 @@
+pub mod resources {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use serde::Deserialize;
+    use crate::game_objects::game_objects::Terrain;
+    use crate::wildlife::wildlife::Species;
+
+    pub const RESOURCES_PATH: &str = "data/resources.ron";
+
+    /// Index of a resource kind in the `ResourceRegistry`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub(crate) struct ResourceId(pub usize);
+
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+    pub(crate) enum GatherMethod {
+        Farm,
+        Gather,
+        Hunt,
+        Craft,
+    }
+
+    #[derive(Clone, Debug, Deserialize)]
+    pub(crate) struct ResourceKind {
+        pub id: String,
+        pub name: String,
+        pub method: GatherMethod,
+        #[serde(default)]
+        pub sources: Vec<Terrain>,
+        #[serde(default)]
+        pub prey: Vec<Species>,
+        #[serde(default)]
+        pub edible: bool,
+        #[serde(default)]
+        pub target: i32,
+        #[serde(default)]
+        pub starting: i32,
+    }
+
+    #[derive(Deserialize)]
+    struct ResourceFile {
+        resources: Vec<ResourceKind>,
+    }
+
+    /// Every resource the game knows about, loaded from `RESOURCES_PATH`.
+    pub struct ResourceRegistry {
+        pub kinds: Vec<ResourceKind>,
+    }
+
+    impl ResourceRegistry {
+        pub fn load() -> ResourceRegistry {
+            let text = fs::read_to_string(RESOURCES_PATH)
+                .unwrap_or_else(|error| panic!("Could not read {}: {}", RESOURCES_PATH, error));
+            return ResourceRegistry::parse(&text);
+        }
+
+        pub fn parse(text: &str) -> ResourceRegistry {
+            let file: ResourceFile = ron::de::from_str(text)
+                .unwrap_or_else(|error| panic!("Could not parse {}: {}", RESOURCES_PATH, error));
+            return ResourceRegistry { kinds: file.resources };
+        }
+
+        /// Looks a resource up by its id in the data files, panicking on unknown ids as that
+        /// is a mistake in the data rather than something the game can recover from.
+        pub fn id(&self, id: &str) -> ResourceId {
+            return match self.kinds.iter().position(|kind| kind.id == id) {
+                Some(index) => ResourceId(index),
+                None => panic!("Unknown resource '{}'", id),
+            };
+        }
+
+        pub fn get(&self, id: ResourceId) -> &ResourceKind {
+            return &self.kinds[id.0];
+        }
+
+        pub fn ids(&self) -> impl Iterator<Item = ResourceId> {
+            return (0..self.kinds.len()).map(ResourceId);
+        }
+
+        pub fn edible(&self) -> Vec<ResourceId> {
+            return self.ids().filter(|id| self.get(*id).edible).collect();
+        }
+
+        pub fn starting_inventory(&self) -> Inventory {
+            let mut inventory = Inventory::new();
+            for id in self.ids() {
+                inventory.add(id, self.get(id).starting);
+            }
+            return inventory;
+        }
+    }
+
+    /// Amounts of any number of resources, used by stockpiles, workshop buffers and villagers' loads.
+    #[derive(Clone, Debug, Default, PartialEq)]
+    pub(crate) struct Inventory {
+        amounts: BTreeMap<ResourceId, i32>,
+    }
+
+    impl Inventory {
+        pub fn new() -> Inventory {
+            return Inventory { amounts: BTreeMap::new() }
+        }
+
+        pub fn get(&self, id: ResourceId) -> i32 {
+            return self.amounts.get(&id).copied().unwrap_or(0);
+        }
+
+        pub fn add(&mut self, id: ResourceId, amount: i32) {
+            if amount != 0 {
+                *self.amounts.entry(id).or_insert(0) += amount;
+            }
+        }
+
+        /// Removes the amount if there is enough of it, returning whether it was taken. Nothing
+        /// is taken of an amount below one.
+        pub fn take(&mut self, id: ResourceId, amount: i32) -> bool {
+            if amount <= 0 || self.get(id) < amount {
+                return false;
+            }
+            self.add(id, -amount);
+            return true;
+        }
+
+        pub fn has_all(&self, amounts: &[(ResourceId, i32)]) -> bool {
+            return amounts.iter().all(|(id, amount)| self.get(*id) >= *amount);
+        }
+
+        pub fn total(&self) -> i32 {
+            return self.amounts.values().sum();
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = (ResourceId, i32)> + '_ {
+            return self.amounts.iter().filter(|(_, amount)| **amount > 0).map(|(id, amount)| (*id, *amount));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn only_what_is_held_can_be_taken() {
+            let mut inventory = Inventory::new();
+            inventory.add(ResourceId(0), 5);
+            assert!(!inventory.take(ResourceId(0), 6));
+            assert!(inventory.take(ResourceId(0), 5));
+            assert_eq!(inventory.get(ResourceId(0)), 0);
+        }
+
+        #[test]
+        fn amounts_below_one_are_never_taken() {
+            let mut inventory = Inventory::new();
+            inventory.add(ResourceId(0), 5);
+            assert!(!inventory.take(ResourceId(0), 0));
+            assert!(!inventory.take(ResourceId(0), -3));
+            assert_eq!(inventory.get(ResourceId(0)), 5);
+        }
+    }
+}
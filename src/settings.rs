@@ -1,18 +1,147 @@
 pub mod settings {
-    use tcod::Color;
-    use tcod::map::FovAlgorithm;
+    use crate::renderer::renderer::Color;
+    use crate::fov::fov::FovAlgorithm;
 
-    pub const MAP_WIDTH: i32 = 1000;
-    pub const MAP_HEIGHT: i32 = 450;
-    pub const GAME_SEED: f64 = 1.5;
+    pub const DEFAULT_SEED: u64 = 1;
 
-    pub const FOV_ALGO: FovAlgorithm = FovAlgorithm::Shadow; // default FOV algorithm
+    pub const FOV_ALGO: FovAlgorithm = FovAlgorithm::Shadowcasting; // default FOV algorithm
     pub const FOV_LIGHT_WALLS: bool = true; // light walls or not
     pub const TORCH_RADIUS: i32 = 15;
+    pub const HOUSE_VISION_RADIUS: i32 = 6;
+    pub const SCOUT_VISION_RADIUS: i32 = 10;
+    pub const WATCHTOWER_VISION_RADIUS: i32 = 25;
+    pub const WATCHTOWER_FOV_ALGO: FovAlgorithm = FovAlgorithm::Permissive; // sees around corners
+    pub const HILL_ELEVATION_BONUS: i32 = 5;
+    pub const MOUNTAIN_ELEVATION_BONUS: i32 = 10;
+
+    pub const HOUSE_COST: i32 = 10;
+    pub const HOUSE_CAPACITY: usize = 5;
+    pub const BIRTH_COST: i32 = 10; // food
+    pub const BIRTH_RADIUS: i32 = 3; // tiles from the house a new villager can appear on
+    pub const WATCHTOWER_COST: i32 = 25;
+    pub const STOCKPILE_COST: i32 = 15;
+    pub const BARRACKS_COST: i32 = 30;
+    pub const GUARD_TOWER_COST: i32 = 25;
+    pub const LIBRARY_COST: i32 = 20;
+    pub const ROAD_COST: i32 = 2;
 
     pub const LIMIT_FPS: i32 = 60; // 60 frames-per-second maximum
 
-    pub const PANEL_HEIGHT: i32 = 7;
+    pub const MILLIS_PER_DAY: u128 = 20000;
+    pub const DAYS_PER_SEASON: i32 = 10;
+    pub const DAYS_PER_YEAR: i32 = DAYS_PER_SEASON * 4;
+    pub const NIGHT_VISION_PERCENT: i32 = 60;
+    pub const NIGHT_DARKNESS: f32 = 0.5;
+
+    pub const WEATHER_MIN_RADIUS: i32 = 8;
+    pub const WEATHER_MAX_RADIUS: i32 = 30;
+    pub const WEATHER_MAX_SPEED: i32 = 4;
+    pub const WEATHER_MIN_DAYS: i32 = 3;
+    pub const WEATHER_MAX_DAYS: i32 = 10;
+    pub const FLOOD_CHANCE: i32 = 20;
+    pub const FLOOD_DAYS: i32 = 4;
+    pub const STORM_DAMAGE_CHANCE: i32 = 5;
+
+    pub const ADULT_AGE: i32 = 16; // years
+    pub const OLD_AGE: i32 = 45; // years, after which villagers may die naturally
+    pub const HUNGER_PER_DAY: i32 = 30;
+    pub const MEAL_SIZE: i32 = 2; // food eaten per meal
+    pub const REST_PER_HARVEST: i32 = 4;
+    pub const REST_PER_SLEEP: i32 = 10;
+    pub const EXHAUSTED_REST: i32 = 20;
+    pub const SOCIAL_PER_DAY: i32 = 20;
+    pub const SOCIAL_PER_CHAT: i32 = 25;
+
+    pub const COMMITMENT_BONUS: i32 = 15; // score bonus for sticking with the current activity
+    pub const CARRY_CAPACITY: i32 = 20;
+    pub const WORK_RADIUS: i32 = 5;
+    pub const GATHER_YIELD: i32 = 4;
+    pub const WORKSHOP_BUFFER: i32 = 10;
+    pub const HUNT_RADIUS: i32 = 12;
+    pub const HUNT_YIELD: i32 = 8;
+    pub const HERD_RADIUS: i32 = 4;
+    pub const ANIMAL_MOVE_TIME: u128 = 1500;
+    pub const BREED_CHANCE: i32 = 15;
+    pub const MIGRATION_CHANCE: i32 = 5;
+    pub const WOLF_STARVE_CHANCE: i32 = 20;
+    pub const WOLF_HUNT_RADIUS: i32 = 10;
+    pub const WOLF_PROWL_RADIUS: i32 = 15;
+    pub const WOLF_PROWL_CHANCE: i32 = 10;
+    pub const PLAYER_HEALTH: i32 = 30;
+    pub const PLAYER_ATTACK: i32 = 6;
+    pub const PLAYER_ARMOUR: i32 = 1;
+    pub const PERSON_HEALTH: i32 = 10;
+    pub const PERSON_ATTACK: i32 = 3;
+    pub const WOLF_HEALTH: i32 = 8;
+    pub const WOLF_ATTACK: i32 = 4;
+    pub const HEAL_PER_DAY: i32 = 2;
+    pub const RIVAL_COUNT: usize = 3;
+    pub const RIVAL_MIN_DISTANCE: i32 = 25;
+    pub const RIVAL_MAX_DISTANCE: i32 = 60;
+    pub const RIVAL_TERRITORY: i32 = 15;
+    pub const CARAVAN_LOAD: i32 = 20;
+    pub const CARAVAN_CHANCE: i32 = 20;
+    pub const TRADE_PACT_CARAVAN_CHANCE: i32 = 40;
+    pub const CARAVAN_STEP_TIME: u128 = 500;
+    pub const CARAVAN_MAX_DAYS: i32 = 20;
+    pub const BASE_PRICE: i32 = 10;
+    pub const MAX_PRICE: i32 = 100;
+    pub const PEACE_TARIFF: i32 = 20;
+    pub const GIFT_SIZE: i32 = 20;
+    pub const TRADE_GOODWILL: i32 = 5;
+    pub const GIFT_GOODWILL: i32 = 15;
+    pub const ENCROACHMENT_PENALTY: i32 = 2;
+    pub const TRADE_PACT_RELATION: i32 = 50;
+    pub const HOSTILE_RELATION: i32 = -50;
+    pub const RAID_GRACE_DAYS: i32 = 20; // no raids before the colony has had time to settle
+    pub const RAID_INTERVAL: i32 = 40; // days between scheduled raids
+    pub const RAID_CHANCE: i32 = 2; // daily chance of a surprise raid
+    pub const RAID_BASE_SIZE: i32 = 2;
+    pub const RAID_POPULATION_PER_RAIDER: i32 = 5;
+    pub const RAID_MIN_DISTANCE: i32 = 25;
+    pub const RAID_MAX_DISTANCE: i32 = 45;
+    pub const RAIDER_MOVE_TIME: u128 = 700;
+    pub const RAIDER_CHASE_RADIUS: i32 = 6;
+    pub const RAIDER_BURN_TIME: u128 = 5000;
+    pub const RAIDER_MAX_DAYS: i32 = 8;
+    pub const RAIDER_HEALTH: i32 = 12;
+    pub const RAIDER_ATTACK: i32 = 5;
+    pub const RAIDER_ARMOUR: i32 = 1;
+    pub const MILITIA_PER_BARRACKS: usize = 4;
+    pub const MILITIA_ATTACK: i32 = 6;
+    pub const MILITIA_ARMOUR: i32 = 2;
+    pub const DEFEND_RADIUS: i32 = 20;
+    pub const GUARD_TOWER_RANGE: i32 = 7;
+    pub const GUARD_TOWER_DAMAGE: i32 = 4;
+    pub const GUARD_TOWER_FIRE_TIME: u128 = 1500;
+    pub const SCHOLARS_PER_LIBRARY: usize = 2;
+    pub const RESEARCH_PER_STUDY: i32 = 2;
+    pub const LABOURERS_PER_EARTHWORK: usize = 2;
+    /// Milliseconds of labour each kind of earthwork takes.
+    pub const CLEAR_FOREST_TIME: u128 = 8000;
+    pub const ROAD_TIME: u128 = 6000;
+    pub const IRRIGATION_TIME: u128 = 10000;
+    pub const TUNNEL_TIME: u128 = 20000;
+    pub const CLEARING_YIELD: i32 = 15; // wood
+    pub const TUNNEL_YIELD: i32 = 10; // stone
+    pub const ROAD_TIME_PERCENT: u128 = 50;
+    pub const IRRIGATION_RADIUS: i32 = 2;
+    pub const IRRIGATION_FERTILITY: i32 = 2;
+    pub const BUILDING_MATERIAL: &str = "wood"; // houses, watchtowers, stockpiles and defences are built from it
+    pub const ROAD_MATERIAL: &str = "stone"; // roads are paved with it and tunnels bring it up
+    pub const SOCIAL_RADIUS: i32 = 10;
+    pub const FLEE_RADIUS: i32 = 8;
+
+    pub const INSPECT_WIDTH: i32 = 32;
+    pub const INSPECT_HEIGHT: i32 = 19; // a score row for each activity a villager weighs, Defend included
+    pub const EVENT_WIDTH: i32 = 70;
+    pub const EVENT_HEIGHT: i32 = 14;
+
+    pub const PANEL_HEIGHT: i32 = 8;
+    pub const STOCK_X: i32 = 40; // stock and raids on the right of the colony details
+    pub const MESSAGES_X: i32 = 74;
+
+    pub const CAMERA_SCROLL_SPEED: i32 = 5;
 
     pub const COLOR_MOUNTAIN: Color = Color {
         r: 244,
@@ -54,4 +183,119 @@ pub mod settings {
         g: 255,
         b: 153,
     };
-}
\ No newline at end of file
+    pub const COLOR_STOCKPILE: Color = Color {
+        r: 196,
+        g: 160,
+        b: 80,
+    };
+    pub const COLOR_DEER: Color = Color {
+        r: 170,
+        g: 110,
+        b: 60,
+    };
+    pub const COLOR_FISH: Color = Color {
+        r: 180,
+        g: 220,
+        b: 255,
+    };
+    pub const COLOR_RAIDER: Color = Color {
+        r: 230,
+        g: 30,
+        b: 30,
+    };
+    pub const COLOR_ROAD: Color = Color {
+        r: 140,
+        g: 115,
+        b: 80,
+    };
+    pub const COLOR_CHANNEL: Color = Color {
+        r: 70,
+        g: 140,
+        b: 210,
+    };
+    pub const COLOR_EARTHWORK: Color = Color {
+        r: 230,
+        g: 200,
+        b: 90,
+    };
+    pub const COLOR_LIBRARY: Color = Color {
+        r: 120,
+        g: 90,
+        b: 200,
+    };
+    pub const COLOR_RESEARCH: Color = Color {
+        r: 170,
+        g: 140,
+        b: 255,
+    };
+    pub const COLOR_DEFENCE: Color = Color {
+        r: 150,
+        g: 150,
+        b: 170,
+    };
+    pub const COLOR_WOLF: Color = Color {
+        r: 150,
+        g: 150,
+        b: 160,
+    };
+    pub const COLOR_COMBAT: Color = Color {
+        r: 255,
+        g: 160,
+        b: 100,
+    };
+    pub const COLOR_DEATH: Color = Color {
+        r: 220,
+        g: 40,
+        b: 40,
+    };
+    pub const COLOR_RIVAL: Color = Color {
+        r: 200,
+        g: 70,
+        b: 60,
+    };
+    pub const COLOR_CARAVAN: Color = Color {
+        r: 230,
+        g: 190,
+        b: 40,
+    };
+    pub const COLOR_NIGHT: Color = Color {
+        r: 10,
+        g: 10,
+        b: 40,
+    };
+    pub const COLOR_WATCHTOWER: Color = Color {
+        r: 120,
+        g: 90,
+        b: 60,
+    };
+    pub const COLOR_UNEXPLORED: Color = Color {
+        r: 242,
+        g: 227,
+        b: 211,
+    };
+    pub const COLOR_MINIMAP_SETTLEMENT: Color = Color {
+        r: 140,
+        g: 40,
+        b: 30,
+    };
+    pub const COLOR_MINIMAP_VIEWPORT: Color = Color {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+    pub const COLOR_UNAFFORDABLE: Color = Color {
+        r: 110,
+        g: 110,
+        b: 110,
+    };
+    pub const COLOR_WORKSHOP: Color = Color {
+        r: 180,
+        g: 120,
+        b: 60,
+    };
+    pub const COLOR_MENU_SELECTED: Color = Color {
+        r: 255,
+        g: 200,
+        b: 60,
+    };
+}
@@ -0,0 +1,388 @@
+pub mod settlements {
+    use std::collections::HashMap;
+    use crate::settings::settings::*;
+    use crate::ai::ai::step_towards;
+    use crate::game_objects::game_objects::{Drawable, Faction, Game, House, Position};
+    use crate::names::names::generate_name;
+    use crate::resources::resources::{Inventory, ResourceId, ResourceRegistry};
+    use crate::stockpile::stockpile::{Stockpile, StockpileSnapshot, add_resources, load_stockpiles, nearest, store_stockpiles, take_resources, total_stock};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use legion::{Entity, IntoQuery};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Diplomacy {
+        Peace,
+        TradePact,
+        Hostile,
+    }
+
+    impl Diplomacy {
+        pub fn name(&self) -> &'static str {
+            return match self {
+                Diplomacy::Peace => "peace",
+                Diplomacy::TradePact => "trade pact",
+                Diplomacy::Hostile => "hostile",
+            };
+        }
+    }
+
+    /// A settlement run by the simulation. `relation` is its feeling towards the player's
+    /// colony, which sets the diplomatic state between them.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Rival {
+        pub name: String,
+        pub faction: Faction,
+        pub home: Position,
+        pub relation: i32,
+    }
+
+    impl Rival {
+        pub fn diplomacy(&self) -> Diplomacy {
+            return if self.relation >= TRADE_PACT_RELATION {
+                Diplomacy::TradePact
+            } else if self.relation <= HOSTILE_RELATION {
+                Diplomacy::Hostile
+            } else {
+                Diplomacy::Peace
+            };
+        }
+    }
+
+    /// Goods on their way from one stockpile to another. A trading caravan sells its cargo
+    /// where it arrives and brings the payment home, a gift is simply handed over.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Caravan {
+        pub home: Entity,
+        pub destination: Entity,
+        pub cargo: Inventory,
+        pub gift: bool,
+        pub returning: bool,
+        pub days_travelled: i32,
+        pub time_since_last_step: u128,
+    }
+
+    /// The rival settlements and the colony's relations with them.
+    pub struct Settlements {
+        rng: StdRng,
+        pub rivals: Vec<Rival>,
+        pub last_report: Option<String>,
+    }
+
+    impl Settlements {
+        pub fn new(seed: u64) -> Settlements {
+            return Settlements {
+                rng: StdRng::seed_from_u64(seed),
+                rivals: Vec::new(),
+                last_report: None,
+            }
+        }
+
+        pub fn rival(&self, faction: Faction) -> Option<&Rival> {
+            return self.rivals.iter().find(|rival| rival.faction == faction);
+        }
+
+        /// Rivals only fall out with the player, never with each other.
+        pub fn is_hostile(&self, a: Faction, b: Faction) -> bool {
+            let other = if a == Faction::PLAYER { b } else if b == Faction::PLAYER { a } else { return false };
+            return self.rival(other).is_some_and(|rival| rival.diplomacy() == Diplomacy::Hostile);
+        }
+
+        pub fn describe(&self) -> String {
+            let rivals: Vec<String> = self.rivals.iter()
+                .map(|rival| format!("{} ({})", rival.name, rival.diplomacy().name()))
+                .collect();
+            return format!("Neighbours: {}", rivals.join(", "));
+        }
+
+        pub fn change_relation(&mut self, faction: Faction, change: i32) {
+            let rival = match self.rivals.iter_mut().find(|rival| rival.faction == faction) {
+                Some(rival) => rival,
+                None => return,
+            };
+            let before = rival.diplomacy();
+            rival.relation = (rival.relation + change).clamp(-100, 100);
+            let after = rival.diplomacy();
+            if before != after {
+                self.last_report = Some(format!("{} is now at {} with us", rival.name, after.name()));
+            }
+        }
+    }
+
+    /// What a settlement holding `stock` would give for a unit of the resource. Prices rise
+    /// as the stock falls below the resource's target and drop once it is above it.
+    pub(crate) fn price(resources: &ResourceRegistry, stock: &Inventory, id: ResourceId) -> i32 {
+        let target = resources.get(id).target;
+        return (BASE_PRICE * target / stock.get(id).max(1)).clamp(1, MAX_PRICE);
+    }
+
+    /// The tradeable resource the stock holds the most of relative to its target, if there
+    /// is a caravan load of it to spare.
+    fn surplus(resources: &ResourceRegistry, stock: &Inventory, except: Option<ResourceId>) -> Option<ResourceId> {
+        return resources.ids()
+            .filter(|id| Some(*id) != except && resources.get(*id).target > 0 && stock.get(*id) >= CARAVAN_LOAD)
+            .max_by_key(|id| stock.get(*id) * 100 / resources.get(*id).target);
+    }
+
+    fn is_settled(game: &Game, x: i32, y: i32) -> bool {
+        return game.map.is_buildable(x, y) && game.map.is_buildable(x + 1, y);
+    }
+
+    /// Founds the rival settlements at a distance from where the player lands, each with a
+    /// house and a stockpile of the same supplies the player starts with.
+    pub(crate) fn spawn_rivals(game: &mut Game, landing: Position) {
+        let mut homes: Vec<Position> = vec![landing];
+        let (width, height) = (game.map.width(), game.map.height());
+        for index in 0..RIVAL_COUNT {
+            let home = (0..200).find_map(|_| {
+                let rng = &mut game.settlements.rng;
+                let x_offset = rng.gen_range(-RIVAL_MAX_DISTANCE, RIVAL_MAX_DISTANCE + 1);
+                let y_offset = rng.gen_range(-RIVAL_MAX_DISTANCE, RIVAL_MAX_DISTANCE + 1);
+                let x = (landing.x + x_offset - width).rem_euclid(width) + width;
+                let y = (landing.y + y_offset - height).rem_euclid(height) + height;
+                let position = Position::new(x, y);
+                let spaced_out = homes.iter().all(|other| other.distance(position) >= RIVAL_MIN_DISTANCE);
+                if spaced_out && is_settled(game, x, y) { Some(position) } else { None }
+            });
+            let home = match home {
+                Some(home) => home,
+                None => continue,
+            };
+            homes.push(home);
+
+            let faction = Faction(index + 1);
+            let name = generate_name(&mut game.settlements.rng);
+            let supplies: Vec<(ResourceId, i32)> = game.resources.starting_inventory().iter().collect();
+            game.map.make_tile_built_on(home.x, home.y);
+            game.map.make_tile_built_on(home.x + 1, home.y);
+            game.world.push((home, Drawable::new('A', COLOR_RIVAL), House::new(), faction));
+            let store = Position::new(home.x + 1, home.y);
+            game.world.push((store, Drawable::new('=', COLOR_RIVAL), Stockpile::new(Inventory::new()), faction));
+            // whatever a rival has no room for is simply never had
+            add_resources(&mut game.world, faction, store, &supplies);
+            game.settlements.rivals.push(Rival { name, faction, home, relation: game.difficulty.starting_relation() });
+        }
+    }
+
+    /// Runs the rivals' daily business: growing their settlements, reacting to the player's
+    /// colony creeping into their land, and sending out trade caravans.
+    pub(crate) fn settlements_system(game: &mut Game) {
+        let mut house_query = <(&House, &Position, &Faction)>::query();
+        let player_houses: Vec<Position> = house_query.iter(&game.world)
+            .filter(|(_, _, faction)| **faction == Faction::PLAYER)
+            .map(|(_, position, _)| *position)
+            .collect();
+
+        for index in 0..game.settlements.rivals.len() {
+            let rival = game.settlements.rivals[index].clone();
+            let encroaching = player_houses.iter().filter(|house| house.distance(rival.home) <= RIVAL_TERRITORY).count() as i32;
+            if encroaching > 0 {
+                game.settlements.change_relation(rival.faction, -ENCROACHMENT_PENALTY * encroaching);
+            }
+            expand(game, &rival);
+            send_caravan(game, &rival);
+        }
+    }
+
+    /// Builds the rival a new house once all of its houses are full.
+    fn expand(game: &mut Game, rival: &Rival) {
+        let mut house_query = <(&House, &Faction)>::query();
+        let full = house_query.iter(&game.world)
+            .filter(|(_, faction)| **faction == rival.faction)
+            .all(|(house, _)| house.is_full());
+        if !full {
+            return;
+        }
+        let rng = &mut game.settlements.rng;
+        let x = rival.home.x + rng.gen_range(-RIVAL_TERRITORY / 2, RIVAL_TERRITORY / 2 + 1);
+        let y = rival.home.y + rng.gen_range(-RIVAL_TERRITORY / 2, RIVAL_TERRITORY / 2 + 1);
+        let cost = vec![(game.resources.id(BUILDING_MATERIAL), HOUSE_COST)];
+        if game.map.is_buildable(x, y) && take_resources(&mut game.world, rival.faction, rival.home, &cost) {
+            game.map.make_tile_built_on(x, y);
+            game.world.push((Position::new(x, y), Drawable::new('A', COLOR_RIVAL), House::new(), rival.faction));
+        }
+    }
+
+    fn send_caravan(game: &mut Game, rival: &Rival) {
+        let chance = if rival.diplomacy() == Diplomacy::TradePact { TRADE_PACT_CARAVAN_CHANCE } else { CARAVAN_CHANCE };
+        if game.settlements.rng.gen_range(0, 100) >= chance {
+            return;
+        }
+        let stockpiles = load_stockpiles(&game.world);
+        let home = match nearest(&stockpiles, rival.faction, rival.home, |_| true) {
+            Some(index) => index,
+            None => return,
+        };
+        let goods = match surplus(&game.resources, &stockpiles[home].stockpile.inventory, None) {
+            Some(goods) => goods,
+            None => return,
+        };
+
+        // the caravan heads for whichever friendly settlement will pay the most for its goods
+        let mut partners = vec![Faction::PLAYER];
+        partners.extend(game.settlements.rivals.iter().map(|other| other.faction));
+        let destination = partners.iter()
+            .filter(|partner| **partner != rival.faction && !game.settlements.is_hostile(rival.faction, **partner))
+            .filter_map(|partner| {
+                let stockpile = nearest(&stockpiles, *partner, rival.home, |_| true)?;
+                let offer = price(&game.resources, &total_stock(&game.world, *partner), goods);
+                Some((stockpile, offer))
+            })
+            .max_by_key(|(_, offer)| *offer)
+            .map(|(stockpile, _)| stockpile);
+        if let Some(destination) = destination {
+            dispatch(game, &stockpiles[home], &stockpiles[destination], goods, CARAVAN_LOAD, false);
+        }
+    }
+
+    fn dispatch(game: &mut Game, home: &StockpileSnapshot, destination: &StockpileSnapshot, goods: ResourceId, amount: i32, gift: bool) {
+        let mut query = <&mut Stockpile>::query();
+        if let Ok(stockpile) = query.get_mut(&mut game.world, home.entity) {
+            if !stockpile.inventory.take(goods, amount) {
+                return;
+            }
+        }
+        let mut cargo = Inventory::new();
+        cargo.add(goods, amount);
+        game.world.push((
+            home.position,
+            Drawable::new('$', COLOR_CARAVAN),
+            home.faction,
+            Caravan {
+                home: home.entity,
+                destination: destination.entity,
+                cargo,
+                gift,
+                returning: false,
+                days_travelled: 0,
+                time_since_last_step: 0,
+            }
+        ));
+    }
+
+    /// Sends a caravan of the colony's most plentiful resource from the stockpile nearest the
+    /// player to the nearest rival, to win back their favour.
+    pub(crate) fn send_gift(game: &mut Game, from: Position) {
+        let stockpiles = load_stockpiles(&game.world);
+        let home = match nearest(&stockpiles, Faction::PLAYER, from, |_| true) {
+            Some(index) => index,
+            None => return,
+        };
+        let goods = match surplus(&game.resources, &stockpiles[home].stockpile.inventory, None) {
+            Some(goods) => goods,
+            None => return,
+        };
+        let destination = game.settlements.rivals.iter()
+            .filter_map(|rival| nearest(&stockpiles, rival.faction, from, |_| true))
+            .min_by_key(|index| from.distance(stockpiles[*index].position));
+        if let Some(destination) = destination {
+            dispatch(game, &stockpiles[home], &stockpiles[destination], goods, GIFT_SIZE, true);
+        }
+    }
+
+    /// Sells the cargo to the stockpile the caravan has reached. The buyer pays, at its own
+    /// prices, with whatever it has most of.
+    fn trade(resources: &ResourceRegistry, caravan: &mut Caravan, buyer: &mut StockpileSnapshot, buyer_stock: &Inventory, tariff: i32) {
+        let goods: Vec<(ResourceId, i32)> = caravan.cargo.iter().collect();
+        let mut value = 0;
+        for (id, amount) in goods.iter() {
+            let delivered = buyer.stockpile.store(*id, *amount);
+            caravan.cargo.take(*id, delivered);
+            value += delivered * price(resources, buyer_stock, *id);
+        }
+        if caravan.gift {
+            return;
+        }
+        let sold = goods.first().map(|(id, _)| *id);
+        if let Some(payment) = surplus(resources, &buyer.stockpile.inventory, sold) {
+            let amount = (value * (100 - tariff) / 100 / price(resources, buyer_stock, payment))
+                .min(buyer.stockpile.inventory.get(payment));
+            buyer.stockpile.inventory.take(payment, amount);
+            caravan.cargo.add(payment, amount);
+        }
+    }
+
+    /// Moves caravans along, trades when they arrive, and unloads them back home. Caravans
+    /// that cannot find their way within `CARAVAN_MAX_DAYS` are lost with their cargo.
+    pub(crate) fn caravan_system(game: &mut Game, time_delta: u128, new_day: bool) {
+        let mut stockpiles = load_stockpiles(&game.world);
+        let index_of: HashMap<Entity, usize> = stockpiles.iter()
+            .enumerate()
+            .map(|(index, snapshot)| (snapshot.entity, index))
+            .collect();
+        let mut finished = Vec::new();
+        let mut goodwill = Vec::new();
+
+        let mut query = <(Entity, &mut Caravan, &mut Position, &Faction)>::query();
+        for (entity, caravan, position, faction) in query.iter_mut(&mut game.world) {
+            if new_day {
+                caravan.days_travelled += 1;
+            }
+            caravan.time_since_last_step += time_delta;
+            if caravan.days_travelled > CARAVAN_MAX_DAYS {
+                finished.push(*entity);
+                continue;
+            }
+            if caravan.time_since_last_step < CARAVAN_STEP_TIME {
+                continue;
+            }
+            caravan.time_since_last_step = 0;
+
+            let target = if caravan.returning { caravan.home } else { caravan.destination };
+            let index = match index_of.get(&target) {
+                Some(index) => *index,
+                None if caravan.returning => {
+                    finished.push(*entity);
+                    continue;
+                }
+                None => {
+                    caravan.returning = true;
+                    continue;
+                }
+            };
+            if position.distance(stockpiles[index].position) > 1 {
+                step_towards(&game.map, position, stockpiles[index].position);
+                continue;
+            }
+
+            if caravan.returning {
+                let load: Vec<(ResourceId, i32)> = caravan.cargo.iter().collect();
+                for (id, amount) in load {
+                    stockpiles[index].stockpile.inventory.add(id, amount);
+                }
+                finished.push(*entity);
+            } else {
+                let buyer = stockpiles[index].faction;
+                let partner = if buyer == Faction::PLAYER { *faction } else { buyer };
+                let tariff = match game.settlements.rival(partner).map(|rival| rival.diplomacy()) {
+                    Some(Diplomacy::TradePact) => 0,
+                    _ => PEACE_TARIFF,
+                };
+                let buyer_stock = total_stock_of(&stockpiles, buyer);
+                trade(&game.resources, caravan, &mut stockpiles[index], &buyer_stock, tariff);
+                if buyer == Faction::PLAYER || *faction == Faction::PLAYER {
+                    goodwill.push((partner, if caravan.gift { GIFT_GOODWILL } else { TRADE_GOODWILL }));
+                }
+                caravan.returning = true;
+            }
+        }
+
+        store_stockpiles(&mut game.world, &stockpiles);
+        for entity in finished {
+            game.world.remove(entity);
+        }
+        for (faction, change) in goodwill {
+            game.settlements.change_relation(faction, change);
+        }
+    }
+
+    fn total_stock_of(stockpiles: &[StockpileSnapshot], faction: Faction) -> Inventory {
+        let mut total = Inventory::new();
+        for snapshot in stockpiles.iter().filter(|snapshot| snapshot.faction == faction) {
+            for (id, amount) in snapshot.stockpile.inventory.iter() {
+                total.add(id, amount);
+            }
+        }
+        return total;
+    }
+}
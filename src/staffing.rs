@@ -0,0 +1,65 @@
+pub mod staffing {
+    use std::collections::{HashMap, HashSet};
+    use crate::game_objects::game_objects::{Faction, Game, Job, Person};
+    use crate::resources::resources::Inventory;
+    use legion::{Entity, IntoQuery, World};
+
+    /// Who was let go and who was taken on by a round of staffing.
+    pub(crate) struct Staffing {
+        pub dismissed: Vec<Entity>,
+        /// The building and the villager taken on there.
+        pub hired: Vec<(Entity, Entity)>,
+    }
+
+    /// Every villager's job, for checking the rosters buildings keep against.
+    pub(crate) fn jobs(world: &World) -> HashMap<Entity, Option<Job>> {
+        let mut query = <(Entity, &Person)>::query();
+        return query.iter(world).map(|(entity, person)| (*entity, person.job)).collect();
+    }
+
+    /// Staffs one kind of building, whose jobs `make_job` makes. Villagers working at a
+    /// building that is no longer `standing` lose their job, then each vacancy, a building
+    /// and the faction it belongs to, is filled by an adult of that faction without a job or
+    /// gathering a resource it already has its target of.
+    pub(crate) fn staff(game: &mut Game, stocks: &HashMap<Faction, Inventory>, standing: &HashSet<Entity>, vacancies: Vec<(Entity, Faction)>, make_job: impl Fn(Entity) -> Job) -> Staffing {
+        let mut dismissed = Vec::new();
+        let mut query = <(Entity, &mut Person)>::query();
+        for (entity, person) in query.iter_mut(&mut game.world) {
+            let workplace = person.job.and_then(|job| job.workplace());
+            if let Some(workplace) = workplace {
+                if person.job == Some(make_job(workplace)) && !standing.contains(&workplace) {
+                    person.job = None;
+                    dismissed.push(*entity);
+                }
+            }
+        }
+
+        let resources = &game.resources;
+        let mut query = <(Entity, &Person, &Faction)>::query();
+        let mut candidates: Vec<(Entity, Faction, bool)> = query.iter(&game.world)
+            .filter(|(_, person, faction)| person.is_adult() && match person.job {
+                None => true,
+                Some(Job::Gatherer(id)) => stocks.get(faction).is_some_and(|stock| stock.get(id) >= resources.get(id).target),
+                _ => false,
+            })
+            .map(|(entity, person, faction)| (*entity, *faction, person.job.is_none()))
+            .collect();
+        // the jobless sort last so they are the first to be taken on
+        candidates.sort_by_key(|(_, _, jobless)| *jobless);
+
+        let mut hired = Vec::new();
+        let mut query = <&mut Person>::query();
+        for (building, faction) in vacancies {
+            let index = match candidates.iter().rposition(|(_, candidate, _)| *candidate == faction) {
+                Some(index) => index,
+                None => continue,
+            };
+            let (villager, _, _) = candidates.remove(index);
+            if let Ok(person) = query.get_mut(&mut game.world, villager) {
+                person.job = Some(make_job(building));
+            }
+            hired.push((building, villager));
+        }
+        return Staffing { dismissed, hired };
+    }
+}
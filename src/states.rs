@@ -0,0 +1,471 @@
+pub mod states {
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::Game;
+    use crate::renderer::renderer::{Layer, Renderer, BLACK, WHITE};
+    use crate::options::options::{Difficulty, MapSize, NewGame, Preferences};
+    use crate::events::events::choose;
+    use crate::input::input::{Event, Key};
+    use crate::{Frontend, new_game, play_frame, render_event, render_game};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    /// Why the game stopped running.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Outcome {
+        Paused,
+        /// A narrative event is waiting on the player's choice.
+        Event,
+        /// The player opened the research screen.
+        Research,
+        PlayerDied,
+        ColonyDied,
+    }
+
+    /// A run in progress, kept together with the simulation's random number generator so it
+    /// can be paused and picked up again.
+    pub(crate) struct Session {
+        pub game: Game,
+        pub rng: StdRng,
+        pub time_of_last_frame: u128,
+    }
+
+    impl Session {
+        /// Starts a run at `time`, in milliseconds on the frontend's clock.
+        pub fn new(game: Game, options: &NewGame, time: u128) -> Session {
+            // the simulation has its own seeded generator so villagers behave the same every run
+            return Session {
+                game,
+                rng: StdRng::seed_from_u64(options.rng_seed(1)),
+                time_of_last_frame: time,
+            }
+        }
+
+        /// Restarts the frame clock so no time passes in the game while it is paused.
+        fn resume(mut self, frontend: &Frontend) -> State {
+            self.time_of_last_frame = frontend.input.elapsed_millis();
+            return State::InGame(self);
+        }
+    }
+
+    /// A list of options stacked in the middle of the screen, moved through with the up and
+    /// down keys.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Menu {
+        pub selected: usize,
+        options: usize,
+    }
+
+    impl Menu {
+        pub fn new(options: usize) -> Menu {
+            return Menu { selected: 0, options }
+        }
+
+        /// Moves the selection on up and down, returning whether the key was used.
+        fn navigate(&mut self, key: Key) -> bool {
+            match key {
+                Key::Up => self.selected = (self.selected + self.options - 1) % self.options,
+                Key::Down => self.selected = (self.selected + 1) % self.options,
+                _ => return false,
+            }
+            return true;
+        }
+
+        fn render(&self, renderer: &mut dyn Renderer, title: &str, lines: &[String]) {
+            let longest = lines.iter().map(|line| line.len()).max().unwrap_or(0).max(title.len());
+            let (width, height) = (longest as i32 + 6, lines.len() as i32 + 4);
+            let (screen_width, screen_height) = renderer.screen_size();
+            let left = screen_width / 2 - width / 2;
+            let top = screen_height / 2 - height / 2;
+            renderer.print_frame(Layer::Screen, (left, top), (width, height), Some(title));
+            for (row, line) in lines.iter().enumerate() {
+                let color = if row == self.selected { COLOR_MENU_SELECTED } else { WHITE };
+                renderer.print_centred(Layer::Screen, left + width / 2, top + 2 + row as i32, line, color);
+            }
+        }
+    }
+
+    const MAIN_MENU: [&str; 3] = ["New game", "Settings", "Quit"];
+    const PAUSE_MENU: [&str; 4] = ["Resume", "Settings", "Quit to main menu", "Quit game"];
+    const NEW_GAME_ROWS: usize = 5;
+    const SETTINGS_ROWS: usize = 3;
+
+    /// The screens the game moves between. Each state draws itself and handles its own input,
+    /// handing back the state to run on the next frame.
+    pub(crate) enum State {
+        MainMenu(Menu),
+        NewGameSetup(NewGame, Menu),
+        Loading(NewGame),
+        InGame(Session),
+        Paused(Session, Menu),
+        Event(Session),
+        Research(Session, Menu),
+        GameOver(Session, Outcome),
+        Settings(Menu, Box<State>),
+        Exit,
+    }
+
+    impl State {
+        pub fn main_menu() -> State {
+            return State::MainMenu(Menu::new(MAIN_MENU.len()));
+        }
+
+        /// Draws the state, then reacts to this frame's input.
+        pub(crate) fn step(mut self, frontend: &mut Frontend, preferences: &mut Preferences) -> State {
+            self.render(frontend, preferences);
+            frontend.renderer.present();
+            return self.update(frontend, preferences);
+        }
+
+        fn render(&mut self, frontend: &mut Frontend, preferences: &Preferences) {
+            match self {
+                State::MainMenu(menu) => {
+                    clear_screen(frontend.renderer.as_mut());
+                    menu.render(frontend.renderer.as_mut(), "Rouge Civ", &lines(&MAIN_MENU));
+                }
+                State::NewGameSetup(options, menu) => {
+                    clear_screen(frontend.renderer.as_mut());
+                    let rows = [
+                        format!("Seed: {}", options.seed),
+                        format!("Map size: {}", options.map_size.name()),
+                        format!("Difficulty: {}", options.difficulty.name()),
+                        String::from("Start"),
+                        String::from("Back"),
+                    ];
+                    menu.render(frontend.renderer.as_mut(), "New game", &rows);
+                    print_hint(frontend.renderer.as_mut(), "Left/Right: change    0-9: type a seed    r: random seed");
+                }
+                State::Loading(options) => {
+                    clear_screen(frontend.renderer.as_mut());
+                    let (width, height) = options.map_size.dimensions();
+                    let text = format!("Generating a {}x{} world from seed {}...", width, height, options.seed);
+                    let (screen_width, screen_height) = frontend.renderer.screen_size();
+                    frontend.renderer.print_centred(Layer::Screen, screen_width / 2, screen_height / 2, &text, WHITE);
+                }
+                State::InGame(session) => render_game(frontend, &mut session.game),
+                State::Paused(session, menu) => {
+                    render_game(frontend, &mut session.game);
+                    menu.render(frontend.renderer.as_mut(), "Paused", &lines(&PAUSE_MENU));
+                }
+                State::Event(session) => {
+                    render_game(frontend, &mut session.game);
+                    render_event(frontend, &session.game);
+                }
+                State::Research(session, menu) => {
+                    render_game(frontend, &mut session.game);
+                    render_research(frontend.renderer.as_mut(), &session.game, menu);
+                }
+                State::GameOver(session, outcome) => render_game_over(frontend.renderer.as_mut(), &session.game, *outcome),
+                State::Settings(menu, _) => {
+                    clear_screen(frontend.renderer.as_mut());
+                    let on_off = |on: bool| if on { "on" } else { "off" };
+                    let rows = [
+                        format!("Fullscreen: {}", on_off(preferences.fullscreen)),
+                        format!("Edge scrolling: {}", on_off(preferences.edge_scrolling)),
+                        String::from("Back"),
+                    ];
+                    menu.render(frontend.renderer.as_mut(), "Settings", &rows);
+                }
+                State::Exit => {}
+            }
+        }
+
+        fn update(self, frontend: &mut Frontend, preferences: &mut Preferences) -> State {
+            return match self {
+                State::MainMenu(menu) => main_menu_input(frontend, menu),
+                State::NewGameSetup(options, menu) => new_game_input(frontend, options, menu),
+                State::Loading(options) => {
+                    let game = new_game(frontend, &options);
+                    State::InGame(Session::new(game, &options, frontend.input.elapsed_millis()))
+                }
+                State::InGame(mut session) => match play_frame(frontend, &mut session, preferences) {
+                    Some(Outcome::Paused) => State::Paused(session, Menu::new(PAUSE_MENU.len())),
+                    Some(Outcome::Event) => State::Event(session),
+                    Some(Outcome::Research) => {
+                        let options = session.game.research.available().len().max(1);
+                        State::Research(session, Menu::new(options))
+                    }
+                    // runs are never saved, so once one is over it is gone for good
+                    Some(outcome) => State::GameOver(session, outcome),
+                    None => State::InGame(session),
+                },
+                State::Paused(session, menu) => pause_input(frontend, session, menu),
+                State::Event(session) => event_input(frontend, session),
+                State::Research(session, menu) => research_input(frontend, session, menu),
+                State::GameOver(session, outcome) => match read_key(frontend) {
+                    Some(Key::Enter) => State::main_menu(),
+                    Some(Key::Escape) => State::Exit,
+                    _ => State::GameOver(session, outcome),
+                },
+                State::Settings(menu, previous) => settings_input(frontend, menu, *previous, preferences),
+                State::Exit => State::Exit,
+            };
+        }
+    }
+
+    fn lines(options: &[&str]) -> Vec<String> {
+        return options.iter().map(|option| option.to_string()).collect();
+    }
+
+    fn clear_screen(renderer: &mut dyn Renderer) {
+        renderer.clear(Layer::Screen, BLACK);
+    }
+
+    fn print_hint(renderer: &mut dyn Renderer, hint: &str) {
+        let (width, height) = renderer.screen_size();
+        renderer.print_centred(Layer::Screen, width / 2, height - 2, hint, WHITE);
+    }
+
+    /// The key pressed this frame, if any. Menus never wait for input so the window keeps
+    /// responding while they are open.
+    fn read_key(frontend: &mut Frontend) -> Option<Key> {
+        return match frontend.poll() {
+            Some(Event::Key { key, .. }) => Some(key),
+            _ => None,
+        };
+    }
+
+    /// The option after or before `current` in `all`, wrapping around at either end.
+    fn cycle<T: Copy + PartialEq>(all: &[T], current: T, forwards: bool) -> T {
+        let index = all.iter().position(|option| *option == current).unwrap_or(0);
+        let step = if forwards { 1 } else { all.len() - 1 };
+        return all[(index + step) % all.len()];
+    }
+
+    fn main_menu_input(frontend: &mut Frontend, mut menu: Menu) -> State {
+        let key = match read_key(frontend) {
+            Some(key) => key,
+            None => return State::MainMenu(menu),
+        };
+        if menu.navigate(key) {
+            return State::MainMenu(menu);
+        }
+        return match key {
+            Key::Enter => match menu.selected {
+                0 => State::NewGameSetup(NewGame::new(), Menu::new(NEW_GAME_ROWS)),
+                1 => State::Settings(Menu::new(SETTINGS_ROWS), Box::new(State::MainMenu(menu))),
+                _ => State::Exit,
+            },
+            Key::Escape => State::Exit,
+            _ => State::MainMenu(menu),
+        };
+    }
+
+    fn new_game_input(frontend: &mut Frontend, mut options: NewGame, mut menu: Menu) -> State {
+        let key = match read_key(frontend) {
+            Some(key) => key,
+            None => return State::NewGameSetup(options, menu),
+        };
+        if menu.navigate(key) {
+            return State::NewGameSetup(options, menu);
+        }
+        match key {
+            Key::Left | Key::Right => {
+                let forwards = key == Key::Right;
+                match menu.selected {
+                    0 if forwards => options.seed = options.seed.wrapping_add(1),
+                    0 => options.seed = options.seed.wrapping_sub(1),
+                    1 => options.map_size = cycle(&MapSize::ALL, options.map_size, forwards),
+                    2 => options.difficulty = cycle(&Difficulty::ALL, options.difficulty, forwards),
+                    _ => {}
+                }
+            }
+            Key::Backspace => options.seed /= 10,
+            Key::Char('r') => options.seed = rand::thread_rng().gen_range(0, 1_000_000),
+            Key::Char(digit) => if let Some(digit) = digit.to_digit(10) {
+                options.seed = options.seed.saturating_mul(10).saturating_add(digit as u64);
+            },
+            Key::Enter if menu.selected == 3 => return State::Loading(options),
+            Key::Enter if menu.selected == 4 => return State::main_menu(),
+            Key::Escape => return State::main_menu(),
+            _ => {}
+        }
+        return State::NewGameSetup(options, menu);
+    }
+
+    fn pause_input(frontend: &mut Frontend, session: Session, mut menu: Menu) -> State {
+        let key = match read_key(frontend) {
+            Some(key) => key,
+            None => return State::Paused(session, menu),
+        };
+        if menu.navigate(key) {
+            return State::Paused(session, menu);
+        }
+        return match key {
+            Key::Enter => match menu.selected {
+                0 => session.resume(frontend),
+                1 => State::Settings(Menu::new(SETTINGS_ROWS), Box::new(State::Paused(session, menu))),
+                2 => State::main_menu(),
+                _ => State::Exit,
+            },
+            Key::Escape => session.resume(frontend),
+            _ => State::Paused(session, menu),
+        };
+    }
+
+    /// Waits for the player to pick one of the event's choices by its number. The rest of the
+    /// game stays frozen behind the popup, though it can still be paused.
+    fn event_input(frontend: &mut Frontend, mut session: Session) -> State {
+        let key = match read_key(frontend) {
+            Some(key) => key,
+            None => return State::Event(session),
+        };
+        if key == Key::Escape {
+            return State::Paused(session, Menu::new(PAUSE_MENU.len()));
+        }
+        let option = match key {
+            Key::Char(digit) => match digit.to_digit(10) {
+                Some(number) if number > 0 => number as usize - 1,
+                _ => return State::Event(session),
+            },
+            _ => return State::Event(session),
+        };
+        return if choose(&mut session.game, option) { session.resume(frontend) } else { State::Event(session) };
+    }
+
+    /// Lists the technologies the colony could study next, with the one being studied marked.
+    fn render_research(renderer: &mut dyn Renderer, game: &Game, menu: &Menu) {
+        let research = &game.research;
+        let mut rows: Vec<String> = research.available().iter()
+            .map(|index| {
+                let marker = if research.current == Some(*index) { "* " } else { "" };
+                format!("{}{}", marker, research.describe_technology(*index, &game.recipes))
+            })
+            .collect();
+        if rows.is_empty() {
+            rows.push(String::from("Nothing left to research"));
+        }
+        menu.render(renderer, "Research", &rows);
+        print_hint(renderer, "Enter: study    Esc: back");
+    }
+
+    fn research_input(frontend: &mut Frontend, mut session: Session, mut menu: Menu) -> State {
+        let key = match read_key(frontend) {
+            Some(key) => key,
+            None => return State::Research(session, menu),
+        };
+        if menu.navigate(key) {
+            return State::Research(session, menu);
+        }
+        return match key {
+            Key::Enter => {
+                if let Some(technology) = session.game.research.available().get(menu.selected) {
+                    session.game.research.select(*technology);
+                }
+                session.resume(frontend)
+            }
+            Key::Escape => session.resume(frontend),
+            _ => State::Research(session, menu),
+        };
+    }
+
+    fn settings_input(frontend: &mut Frontend, mut menu: Menu, previous: State, preferences: &mut Preferences) -> State {
+        let key = match read_key(frontend) {
+            Some(key) => key,
+            None => return State::Settings(menu, Box::new(previous)),
+        };
+        if menu.navigate(key) {
+            return State::Settings(menu, Box::new(previous));
+        }
+        match key {
+            Key::Enter | Key::Left | Key::Right => match menu.selected {
+                0 => {
+                    preferences.fullscreen = !preferences.fullscreen;
+                    frontend.renderer.set_fullscreen(preferences.fullscreen);
+                }
+                1 => preferences.edge_scrolling = !preferences.edge_scrolling,
+                _ if key == Key::Enter => return previous,
+                _ => {}
+            },
+            Key::Escape => return previous,
+            _ => {}
+        }
+        return State::Settings(menu, Box::new(previous));
+    }
+
+    /// Shows how the run went.
+    fn render_game_over(renderer: &mut dyn Renderer, game: &Game, outcome: Outcome) {
+        let headline = match outcome {
+            Outcome::PlayerDied => "You have died",
+            _ => "Your colony has perished",
+        };
+        let days = game.calendar.days_elapsed;
+        let lines = [
+            String::from(headline),
+            String::new(),
+            format!("Survived {} years and {} days", days / DAYS_PER_YEAR, days % DAYS_PER_YEAR),
+            format!("Peak population {}", game.stats.peak_population),
+            format!("Buildings built {}", game.stats.buildings_built),
+            String::new(),
+            String::from("Enter: main menu    Esc: quit"),
+        ];
+
+        let (width, height) = renderer.screen_size();
+        let top = height / 2 - lines.len() as i32 / 2;
+        clear_screen(renderer);
+        for (row, line) in lines.iter().enumerate() {
+            renderer.print_centred(Layer::Screen, width / 2, top + row as i32, line, WHITE);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::input::input::Mouse;
+        use crate::memory_renderer::memory_renderer::{MemoryRenderer, ScriptedInput};
+        use crate::render_cache::render_cache::RenderCache;
+
+        fn frontend(events: &[Event]) -> Frontend {
+            return Frontend {
+                renderer: Box::new(MemoryRenderer::new(30, 9)),
+                input: Box::new(ScriptedInput::new(events)),
+                mouse: None,
+                render_cache: RenderCache::new(1, 1),
+            };
+        }
+
+        fn press(key: Key) -> Event {
+            return Event::Key { key, alt: false };
+        }
+
+        #[test]
+        fn main_menu_is_centred_on_the_screen() {
+            let mut renderer = MemoryRenderer::new(30, 9);
+            clear_screen(&mut renderer);
+            Menu::new(MAIN_MENU.len()).render(&mut renderer, "Rouge Civ", &lines(&MAIN_MENU));
+            assert_eq!(renderer.snapshot(), [
+                "                              ",
+                "        +- Rouge Civ -+       ",
+                "        |             |       ",
+                "        |  New game   |       ",
+                "        |  Settings   |       ",
+                "        |    Quit     |       ",
+                "        |             |       ",
+                "        +-------------+       ",
+                "                              ",
+            ].join("\n"));
+        }
+
+        #[test]
+        fn selected_option_is_highlighted() {
+            let mut renderer = MemoryRenderer::new(30, 9);
+            let mut menu = Menu::new(MAIN_MENU.len());
+            menu.navigate(Key::Down);
+            menu.render(&mut renderer, "Rouge Civ", &lines(&MAIN_MENU));
+            assert_eq!(renderer.cell(Layer::Screen, 11, 3).foreground, WHITE);
+            assert_eq!(renderer.cell(Layer::Screen, 11, 4).foreground, COLOR_MENU_SELECTED);
+        }
+
+        #[test]
+        fn menus_follow_the_frontend_input() {
+            let mouse = Mouse { x: 3, y: 4, left_pressed: false };
+            let mut frontend = frontend(&[Event::Mouse(mouse), press(Key::Down), press(Key::Enter)]);
+            let mut preferences = Preferences::new();
+            let mut state = State::main_menu();
+            for _ in 0..3 {
+                state = state.step(&mut frontend, &mut preferences);
+            }
+            assert!(matches!(state, State::Settings(_, _)));
+            assert_eq!(frontend.mouse, Some(mouse));
+        }
+    }
+}
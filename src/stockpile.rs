@@ -0,0 +1,163 @@
+pub mod stockpile {
+    use crate::game_objects::game_objects::{Faction, Game, Position};
+    use crate::settings::settings::COLOR_UNAFFORDABLE;
+    use crate::resources::resources::{Inventory, ResourceId};
+    use legion::{Entity, World, IntoQuery};
+
+    /// A storage building. Gathered resources only count towards the colony once a villager
+    /// has carried them here.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Stockpile {
+        pub inventory: Inventory,
+        pub capacity: i32,
+    }
+
+    impl Stockpile {
+        pub const CAPACITY: i32 = 300; // shared between every resource, with anything beyond it left out
+
+        pub fn new(inventory: Inventory) -> Stockpile {
+            return Stockpile { inventory, capacity: Stockpile::CAPACITY }
+        }
+
+        pub fn space(&self) -> i32 {
+            return (self.capacity - self.inventory.total()).max(0);
+        }
+
+        /// Adds as much of the amount as there is room for, returning how much that was.
+        pub fn store(&mut self, id: ResourceId, amount: i32) -> i32 {
+            let stored = amount.min(self.space());
+            self.inventory.add(id, stored);
+            return stored;
+        }
+
+        /// The first of the given resources this stockpile holds at least `amount` of.
+        pub fn first_of(&self, ids: &[ResourceId], amount: i32) -> Option<ResourceId> {
+            return ids.iter().copied().find(|id| self.inventory.get(*id) >= amount);
+        }
+    }
+
+    /// A copy of a stockpile's state that systems can read and change while the world is
+    /// mutably borrowed, to be written back with `store_stockpiles` afterwards.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct StockpileSnapshot {
+        pub entity: Entity,
+        pub position: Position,
+        pub faction: Faction,
+        pub stockpile: Stockpile,
+    }
+
+    pub(crate) fn load_stockpiles(world: &World) -> Vec<StockpileSnapshot> {
+        let mut query = <(Entity, &Stockpile, &Position, &Faction)>::query();
+        return query.iter(world)
+            .map(|(entity, stockpile, position, faction)| StockpileSnapshot {
+                entity: *entity,
+                position: *position,
+                faction: *faction,
+                stockpile: stockpile.clone(),
+            })
+            .collect();
+    }
+
+    pub(crate) fn store_stockpiles(world: &mut World, snapshots: &[StockpileSnapshot]) {
+        let mut query = <&mut Stockpile>::query();
+        for snapshot in snapshots {
+            if let Ok(stockpile) = query.get_mut(world, snapshot.entity) {
+                *stockpile = snapshot.stockpile.clone();
+            }
+        }
+    }
+
+    /// The index of the closest of the faction's stockpiles matching the predicate.
+    pub(crate) fn nearest<F>(snapshots: &[StockpileSnapshot], faction: Faction, from: Position, predicate: F) -> Option<usize>
+        where F: Fn(&Stockpile) -> bool {
+        return snapshots.iter()
+            .enumerate()
+            .filter(|(_, snapshot)| snapshot.faction == faction && predicate(&snapshot.stockpile))
+            .min_by_key(|(_, snapshot)| from.distance(snapshot.position))
+            .map(|(index, _)| index);
+    }
+
+    /// Everything held in the faction's stockpiles.
+    pub(crate) fn total_stock(world: &World, faction: Faction) -> Inventory {
+        let mut total = Inventory::new();
+        let mut query = <(&Stockpile, &Faction)>::query();
+        for (stockpile, _) in query.iter(world).filter(|(_, owner)| **owner == faction) {
+            for (id, amount) in stockpile.inventory.iter() {
+                total.add(id, amount);
+            }
+        }
+        return total;
+    }
+
+    /// Puts goods into the faction's stockpiles, nearest first, for as long as they have room,
+    /// returning how much there was no room for.
+    pub(crate) fn add_resources(world: &mut World, faction: Faction, from: Position, goods: &[(ResourceId, i32)]) -> i32 {
+        let mut snapshots = load_stockpiles(world);
+        snapshots.retain(|snapshot| snapshot.faction == faction);
+        snapshots.sort_by_key(|snapshot| from.distance(snapshot.position));
+        let mut unstored = 0;
+        for (id, amount) in goods {
+            let mut remaining = *amount;
+            for snapshot in snapshots.iter_mut() {
+                remaining -= snapshot.stockpile.store(*id, remaining);
+            }
+            unstored += remaining;
+        }
+        store_stockpiles(world, &snapshots);
+        return unstored;
+    }
+
+    /// Puts goods into the player's stockpiles, telling the player about any left out for
+    /// want of room.
+    pub(crate) fn store_goods(game: &mut Game, from: Position, goods: &[(ResourceId, i32)]) {
+        let unstored = add_resources(&mut game.world, Faction::PLAYER, from, goods);
+        if unstored > 0 {
+            game.messages.add(format!("The stockpiles are full, and {} goods were left out", unstored), COLOR_UNAFFORDABLE);
+        }
+    }
+
+    /// Pays a cost out of the faction's stockpiles, nearest first, when they hold enough
+    /// between them.
+    pub(crate) fn take_resources(world: &mut World, faction: Faction, from: Position, cost: &[(ResourceId, i32)]) -> bool {
+        if !total_stock(world, faction).has_all(cost) {
+            return false;
+        }
+        let mut snapshots = load_stockpiles(world);
+        snapshots.retain(|snapshot| snapshot.faction == faction);
+        snapshots.sort_by_key(|snapshot| from.distance(snapshot.position));
+        for (id, amount) in cost {
+            let mut remaining = *amount;
+            for snapshot in snapshots.iter_mut() {
+                let taken = remaining.min(snapshot.stockpile.inventory.get(*id));
+                snapshot.stockpile.inventory.take(*id, taken);
+                remaining -= taken;
+            }
+        }
+        store_stockpiles(world, &snapshots);
+        return true;
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn only_what_there_is_room_for_is_stored() {
+            let mut stockpile = Stockpile::new(Inventory::new());
+            assert_eq!(stockpile.store(ResourceId(0), Stockpile::CAPACITY - 10), Stockpile::CAPACITY - 10);
+            assert_eq!(stockpile.store(ResourceId(1), 25), 10);
+            assert_eq!(stockpile.inventory.get(ResourceId(1)), 10);
+            assert_eq!(stockpile.space(), 0);
+        }
+
+        #[test]
+        fn an_overfull_stockpile_has_no_space() {
+            let mut supplies = Inventory::new();
+            supplies.add(ResourceId(0), Stockpile::CAPACITY + 50);
+            let mut stockpile = Stockpile::new(supplies);
+            assert_eq!(stockpile.space(), 0);
+            assert_eq!(stockpile.store(ResourceId(1), 5), 0);
+            assert_eq!(stockpile.inventory.get(ResourceId(1)), 0);
+        }
+    }
+}
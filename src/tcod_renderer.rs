@@ -0,0 +1,153 @@
+pub mod tcod_renderer {
+    use std::collections::HashMap;
+    use crate::renderer::renderer::{Color, Layer, Renderer};
+    use crate::input::input::{Event, Input, Key, Mouse};
+    use tcod::console::{blit, BackgroundFlag, Console, Offscreen, Root, TextAlignment};
+    use tcod::input::{self, KeyCode};
+    use tcod::system::get_elapsed_time;
+
+    /// Draws through libtcod, with the screen on the root console and every other layer on
+    /// an offscreen console of its own.
+    pub struct TcodRenderer {
+        root: Root,
+        layers: HashMap<Layer, Offscreen>,
+    }
+
+    impl TcodRenderer {
+        pub fn new(root: Root) -> TcodRenderer {
+            return TcodRenderer { root, layers: HashMap::new() };
+        }
+    }
+
+    fn convert(color: Color) -> tcod::Color {
+        return tcod::Color::new(color.r, color.g, color.b);
+    }
+
+    /// Runs the body with `$console` bound to the console behind a layer. The root and the
+    /// offscreen consoles are different types, so the body is written out for each.
+    macro_rules! on_console {
+        ($renderer:expr, $layer:expr, |$console:ident| $body:expr) => {
+            match $layer {
+                Layer::Screen => {
+                    let $console = &mut $renderer.root;
+                    $body
+                }
+                layer => {
+                    let $console = $renderer.layers.get_mut(&layer).unwrap_or_else(|| panic!("No {:?} layer", layer));
+                    $body
+                }
+            }
+        };
+    }
+
+    impl Renderer for TcodRenderer {
+        fn screen_size(&self) -> (i32, i32) {
+            return (self.root.width(), self.root.height());
+        }
+
+        fn create_layer(&mut self, layer: Layer, width: i32, height: i32) {
+            self.layers.insert(layer, Offscreen::new(width, height));
+        }
+
+        fn clear(&mut self, layer: Layer, background: Color) {
+            on_console!(self, layer, |console| {
+                console.set_default_background(convert(background));
+                console.set_default_foreground(tcod::colors::WHITE);
+                console.clear();
+            });
+        }
+
+        fn put_glyph(&mut self, layer: Layer, x: i32, y: i32, glyph: char, color: Color) {
+            on_console!(self, layer, |console| {
+                console.set_default_foreground(convert(color));
+                console.put_char(x, y, glyph, BackgroundFlag::None);
+            });
+        }
+
+        fn set_background(&mut self, layer: Layer, x: i32, y: i32, color: Color) {
+            on_console!(self, layer, |console| console.set_char_background(x, y, convert(color), BackgroundFlag::Set));
+        }
+
+        fn print(&mut self, layer: Layer, x: i32, y: i32, text: &str, color: Color) {
+            on_console!(self, layer, |console| {
+                console.set_default_foreground(convert(color));
+                console.print(x, y, text);
+            });
+        }
+
+        fn print_centred(&mut self, layer: Layer, x: i32, y: i32, text: &str, color: Color) {
+            on_console!(self, layer, |console| {
+                console.set_default_foreground(convert(color));
+                console.print_ex(x, y, BackgroundFlag::None, TextAlignment::Center, text);
+            });
+        }
+
+        fn print_wrapped(&mut self, layer: Layer, position: (i32, i32), size: (i32, i32), text: &str, color: Color) -> i32 {
+            return on_console!(self, layer, |console| {
+                console.set_default_foreground(convert(color));
+                console.print_rect(position.0, position.1, size.0, size.1, text)
+            });
+        }
+
+        fn print_frame(&mut self, layer: Layer, position: (i32, i32), size: (i32, i32), title: Option<&str>) {
+            on_console!(self, layer, |console| {
+                console.set_default_foreground(tcod::colors::WHITE);
+                console.set_default_background(tcod::colors::BLACK);
+                console.print_frame(position.0, position.1, size.0, size.1, true, BackgroundFlag::Set, title);
+            });
+        }
+
+        fn compose(&mut self, layer: Layer, source: (i32, i32), size: (i32, i32), destination: (i32, i32), opacity: f32) {
+            let console = self.layers.get(&layer).unwrap_or_else(|| panic!("No {:?} layer", layer));
+            blit(console, source, size, &mut self.root, destination, opacity, opacity);
+        }
+
+        fn present(&mut self) {
+            self.root.flush();
+        }
+
+        fn set_fullscreen(&mut self, fullscreen: bool) {
+            self.root.set_fullscreen(fullscreen);
+        }
+
+        fn is_closed(&self) -> bool {
+            return self.root.window_closed();
+        }
+    }
+
+    /// Reads the keyboard and mouse through libtcod, whose clock starts with the window.
+    pub struct TcodInput;
+
+    fn convert_key(key: input::Key) -> Option<Key> {
+        return match key.code {
+            KeyCode::Up => Some(Key::Up),
+            KeyCode::Down => Some(Key::Down),
+            KeyCode::Left => Some(Key::Left),
+            KeyCode::Right => Some(Key::Right),
+            KeyCode::Enter => Some(Key::Enter),
+            KeyCode::Escape => Some(Key::Escape),
+            KeyCode::Backspace => Some(Key::Backspace),
+            KeyCode::Spacebar => Some(Key::Space),
+            KeyCode::Text => key.text().chars().next().map(Key::Char),
+            _ => None,
+        };
+    }
+
+    impl Input for TcodInput {
+        fn poll(&mut self) -> Option<Event> {
+            return match input::check_for_event(input::KEY_PRESS | input::MOUSE) {
+                Some((_, input::Event::Key(key))) => convert_key(key).map(|code| Event::Key { key: code, alt: key.alt }),
+                Some((_, input::Event::Mouse(mouse))) => Some(Event::Mouse(Mouse {
+                    x: mouse.cx as i32,
+                    y: mouse.cy as i32,
+                    left_pressed: mouse.lbutton_pressed,
+                })),
+                None => None,
+            };
+        }
+
+        fn elapsed_millis(&self) -> u128 {
+            return get_elapsed_time().as_millis();
+        }
+    }
+}
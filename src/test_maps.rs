@@ -0,0 +1,30 @@
+pub mod test_maps {
+    use crate::game_objects::game_objects::{GameMap, Tile};
+
+    /// Builds a map from rows of `.` for meadow, `#` for mountains and `~` for water, copied
+    /// nine times over like a generated map, with an origin marked `@` in the middle copy.
+    /// Tests give positions in the middle copy too.
+    pub(crate) fn map_from(rows: &[&str]) -> (GameMap, (i32, i32)) {
+        let (width, height) = (rows[0].len(), rows.len());
+        let mut tiles = vec![vec![Tile::meadow(); height * 3]; width * 3];
+        let mut origin = (0, 0);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                if cell == '@' {
+                    origin = ((x + width) as i32, (y + height) as i32);
+                }
+                let tile = match cell {
+                    '#' => Tile::mountain(),
+                    '~' => Tile::water(),
+                    _ => continue,
+                };
+                for i in 0..3 {
+                    for j in 0..3 {
+                        tiles[x + width * i][y + height * j] = tile;
+                    }
+                }
+            }
+        }
+        return (GameMap::new(tiles), origin);
+    }
+}
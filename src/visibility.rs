@@ -0,0 +1,111 @@
+pub mod visibility {
+    use std::collections::HashMap;
+    use crate::settings::settings::*;
+    use crate::game_objects::game_objects::{GameMap, ListenerId, Position, TileListener};
+    use crate::fov::fov::{FovAlgorithm, compute_fov};
+    use legion::Entity;
+
+    /// A single vision source as seen by the FOV computation, with any elevation bonus from
+    /// the terrain it stands on already added to the radius.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct VisionSource {
+        pub position: Position,
+        pub radius: i32,
+        pub algorithm: FovAlgorithm,
+    }
+
+    /// The merged field of view of every entity with `Vision`. Each source is computed in
+    /// turn and its lit cells are copied into this shared layer.
+    pub struct Visibility {
+        width: i32,
+        height: i32,
+        visible: Vec<bool>,
+        visible_cells: Vec<(i32, i32)>,
+        sources: HashMap<Entity, VisionSource>,
+        /// The cells each source lit when it was last computed.
+        lit: HashMap<Entity, Vec<(i32, i32)>>,
+        listener: ListenerId,
+        /// Tiles that changed how far can be seen past them since the last computation.
+        changed_tiles: Vec<(i32, i32)>,
+    }
+
+    impl Visibility {
+        /// Creates the layer for a map, registering it to hear about the map's changes.
+        pub fn new(map: &mut GameMap) -> Visibility {
+            let (width, height) = (map.width() * 3, map.height() * 3);
+            return Visibility {
+                width,
+                height,
+                visible: vec![false; (width * height) as usize],
+                visible_cells: Vec::new(),
+                sources: HashMap::new(),
+                lit: HashMap::new(),
+                listener: map.register_listener(),
+                changed_tiles: Vec::new(),
+            }
+        }
+
+        fn in_bounds(&self, x: i32, y: i32) -> bool {
+            return x >= 0 && y >= 0 && x < self.width && y < self.height;
+        }
+
+        pub fn is_visible(&self, x: i32, y: i32) -> bool {
+            return self.in_bounds(x, y) && self.visible[(x + y * self.width) as usize];
+        }
+
+        pub fn visible_cells(&self) -> &[(i32, i32)] {
+            return &self.visible_cells;
+        }
+
+        /// True when the map changed or a vision source appeared, moved or disappeared since the
+        /// last computation.
+        pub fn is_stale(&self, sources: &HashMap<Entity, VisionSource>) -> bool {
+            return !self.changed_tiles.is_empty() || *sources != self.sources;
+        }
+
+        /// Works out the field of view again for every source that is new, has moved or has a
+        /// changed tile within its radius, keeping what the rest lit last time.
+        pub fn recompute(&mut self, map: &GameMap, sources: HashMap<Entity, VisionSource>) {
+            let changed_tiles = std::mem::take(&mut self.changed_tiles);
+            let mut lit = HashMap::new();
+            for (entity, source) in sources.iter() {
+                let in_reach = changed_tiles.iter()
+                    .any(|(x, y)| source.position.distance(Position::new(*x, *y)) <= source.radius);
+                let cells = match self.lit.remove(entity) {
+                    Some(cells) if !in_reach && self.sources.get(entity) == Some(source) => cells,
+                    _ => {
+                        let Position { x: source_x, y: source_y } = source.position;
+                        compute_fov(map, source_x, source_y, source.radius, FOV_LIGHT_WALLS, source.algorithm)
+                            .into_iter()
+                            .filter(|(x, y)| self.in_bounds(*x, *y))
+                            .collect()
+                    }
+                };
+                lit.insert(*entity, cells);
+            }
+
+            for (x, y) in self.visible_cells.drain(..) {
+                self.visible[(x + y * self.width) as usize] = false;
+            }
+            for &(x, y) in lit.values().flatten() {
+                let index = (x + y * self.width) as usize;
+                if !self.visible[index] {
+                    self.visible[index] = true;
+                    self.visible_cells.push((x, y));
+                }
+            }
+            self.lit = lit;
+            self.sources = sources;
+        }
+    }
+
+    impl TileListener for Visibility {
+        fn listener_id(&self) -> ListenerId {
+            return self.listener;
+        }
+
+        fn tile_changed(&mut self, x: i32, y: i32, _transparent: bool, _walkable: bool) {
+            self.changed_tiles.push((x, y));
+        }
+    }
+}
@@ -0,0 +1,223 @@
+pub mod weather {
+    use crate::settings::settings::*;
+    use crate::calendar::calendar::Season;
+    use crate::game_objects::game_objects::{GameMap, House, Position, Watchtower, destroy_building};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use legion::{Entity, World, IntoQuery};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum FrontKind {
+        Rain,
+        Drought,
+        Storm,
+    }
+
+    impl FrontKind {
+        pub fn name(&self) -> &'static str {
+            return match self {
+                FrontKind::Rain => "rain",
+                FrontKind::Drought => "drought",
+                FrontKind::Storm => "storm",
+            };
+        }
+
+        /// Percentage applied to the fertility of tiles under the front.
+        pub fn fertility_percent(&self) -> i32 {
+            return match self {
+                FrontKind::Rain => 150,
+                FrontKind::Drought => 25,
+                FrontKind::Storm => 100,
+            };
+        }
+    }
+
+    /// A patch of weather drifting across the map. Positions are kept in the centre copy.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Front {
+        pub kind: FrontKind,
+        pub x: i32,
+        pub y: i32,
+        pub radius: i32,
+        pub x_velocity: i32,
+        pub y_velocity: i32,
+        pub days_left: i32,
+    }
+
+    impl Front {
+        /// Whether the front is over a tile of a map of the given size.
+        pub fn covers(&self, x: i32, y: i32, (width, height): (i32, i32)) -> bool {
+            let x_distance = wrapped_distance(self.x, x, width);
+            let y_distance = wrapped_distance(self.y, y, height);
+            return x_distance * x_distance + y_distance * y_distance <= self.radius * self.radius;
+        }
+    }
+
+    fn wrapped_distance(a: i32, b: i32, size: i32) -> i32 {
+        let distance = (a - b).rem_euclid(size);
+        return distance.min(size - distance);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Flood {
+        x: i32,
+        y: i32,
+        days_left: i32,
+    }
+
+    /// Weather simulation stepped once per game day.
+    pub struct Weather {
+        rng: StdRng,
+        map_size: (i32, i32),
+        pub fronts: Vec<Front>,
+        floods: Vec<Flood>,
+        pub last_report: Option<String>,
+    }
+
+    impl Weather {
+        pub fn new(seed: u64, map_width: i32, map_height: i32) -> Weather {
+            return Weather {
+                rng: StdRng::seed_from_u64(seed),
+                map_size: (map_width, map_height),
+                fronts: Vec::new(),
+                floods: Vec::new(),
+                last_report: None,
+            }
+        }
+
+        /// Percentage of normal fertility at a tile given the fronts currently over it.
+        pub fn fertility_percent(&self, x: i32, y: i32) -> i32 {
+            let mut percent = 100;
+            for front in self.fronts.iter().filter(|front| front.covers(x, y, self.map_size)) {
+                percent = percent * front.kind.fertility_percent() / 100;
+            }
+            return percent;
+        }
+
+        pub fn describe(&self) -> String {
+            let count = |kind: FrontKind| self.fronts.iter().filter(|front| front.kind == kind).count();
+            return format!(
+                "Weather: {} rain, {} drought, {} storm, {} flooded",
+                count(FrontKind::Rain),
+                count(FrontKind::Drought),
+                count(FrontKind::Storm),
+                self.floods.len()
+            );
+        }
+
+        pub fn daily_update(&mut self, map: &mut GameMap, world: &mut World, season: Season) {
+            self.recede_floods(map);
+            self.move_fronts();
+            self.spawn_front(season);
+
+            let rain: Vec<Front> = self.fronts.iter()
+                .filter(|front| front.kind != FrontKind::Drought)
+                .copied()
+                .collect();
+            for front in rain.iter() {
+                self.flood_low_land(map, front);
+            }
+
+            let storms: Vec<Front> = self.fronts.iter()
+                .filter(|front| front.kind == FrontKind::Storm)
+                .copied()
+                .collect();
+            for storm in storms.iter() {
+                self.damage_buildings(map, world, storm);
+            }
+        }
+
+        fn move_fronts(&mut self) {
+            let (width, height) = self.map_size;
+            for front in self.fronts.iter_mut() {
+                front.x = (front.x + front.x_velocity - width).rem_euclid(width) + width;
+                front.y = (front.y + front.y_velocity - height).rem_euclid(height) + height;
+                front.days_left -= 1;
+            }
+            self.fronts.retain(|front| front.days_left > 0);
+        }
+
+        fn spawn_front(&mut self, season: Season) {
+            let (rain_chance, drought_chance, storm_chance) = match season {
+                Season::Spring => (30, 5, 5),
+                Season::Summer => (10, 25, 10),
+                Season::Autumn => (25, 5, 20),
+                Season::Winter => (15, 0, 15),
+            };
+            let roll = self.rng.gen_range(0, 100);
+            let kind = if roll < rain_chance {
+                FrontKind::Rain
+            } else if roll < rain_chance + drought_chance {
+                FrontKind::Drought
+            } else if roll < rain_chance + drought_chance + storm_chance {
+                FrontKind::Storm
+            } else {
+                return;
+            };
+
+            let (width, height) = self.map_size;
+            let front = Front {
+                kind,
+                x: self.rng.gen_range(0, width) + width,
+                y: self.rng.gen_range(0, height) + height,
+                radius: self.rng.gen_range(WEATHER_MIN_RADIUS, WEATHER_MAX_RADIUS),
+                x_velocity: self.rng.gen_range(-WEATHER_MAX_SPEED, WEATHER_MAX_SPEED + 1),
+                y_velocity: self.rng.gen_range(-WEATHER_MAX_SPEED, WEATHER_MAX_SPEED + 1),
+                days_left: self.rng.gen_range(WEATHER_MIN_DAYS, WEATHER_MAX_DAYS),
+            };
+            self.last_report = Some(format!("A {} front is forming", kind.name()));
+            self.fronts.push(front);
+        }
+
+        fn recede_floods(&mut self, map: &mut GameMap) {
+            for flood in self.floods.iter_mut() {
+                flood.days_left -= 1;
+                if flood.days_left <= 0 {
+                    map.set_flooded(false, flood.x, flood.y);
+                }
+            }
+            self.floods.retain(|flood| flood.days_left > 0);
+        }
+
+        /// Heavy rain spills water onto low lying land bordering the sea.
+        fn flood_low_land(&mut self, map: &mut GameMap, front: &Front) {
+            let mut flooded = 0;
+            for y in front.y - front.radius..=front.y + front.radius {
+                for x in front.x - front.radius..=front.x + front.radius {
+                    if !front.covers(x, y, self.map_size) || !map.is_buildable(x, y) || map.elevation(x, y) > 0 {
+                        continue;
+                    }
+                    let next_to_water = [(0, -1), (0, 1), (-1, 0), (1, 0)].iter()
+                        .any(|(x_offset, y_offset)| map.is_water(x + x_offset, y + y_offset));
+                    if next_to_water && self.rng.gen_range(0, 100) < FLOOD_CHANCE {
+                        map.set_flooded(true, x, y);
+                        self.floods.push(Flood { x, y, days_left: FLOOD_DAYS });
+                        flooded += 1;
+                    }
+                }
+            }
+            if flooded > 0 {
+                self.last_report = Some(format!("{} tiles flooded by heavy {}", flooded, front.kind.name()));
+            }
+        }
+
+        fn damage_buildings(&mut self, map: &mut GameMap, world: &mut World, storm: &Front) {
+            let mut buildings: Vec<(Entity, Position)> = Vec::new();
+            let mut house_query = <(Entity, &House, &Position)>::query();
+            for (entity, _, position) in house_query.iter(world) {
+                buildings.push((*entity, *position));
+            }
+            let mut tower_query = <(Entity, &Watchtower, &Position)>::query();
+            for (entity, _, position) in tower_query.iter(world) {
+                buildings.push((*entity, *position));
+            }
+
+            for (entity, position) in buildings {
+                if storm.covers(position.x, position.y, self.map_size) && self.rng.gen_range(0, 100) < STORM_DAMAGE_CHANCE {
+                    destroy_building(world, map, entity);
+                    self.last_report = Some(String::from("A storm has torn down a building"));
+                }
+            }
+        }
+    }
+}
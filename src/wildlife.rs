@@ -0,0 +1,249 @@
+pub mod wildlife {
+    use std::collections::HashMap;
+    use crate::settings::settings::*;
+    use crate::combat::combat::arm;
+    use crate::game_objects::game_objects::{Danger, Drawable, GameMap, Person, Position, Terrain};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use legion::{Entity, World, IntoQuery};
+    use serde::Deserialize;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+    pub(crate) enum Species {
+        Deer,
+        Fish,
+        Wolf,
+    }
+
+    impl Species {
+        pub const ALL: [Species; 3] = [Species::Deer, Species::Fish, Species::Wolf];
+
+        pub fn name(&self) -> &'static str {
+            return match self {
+                Species::Deer => "deer",
+                Species::Fish => "fish",
+                Species::Wolf => "wolf",
+            };
+        }
+
+        pub fn drawable(&self) -> Drawable {
+            return match self {
+                Species::Deer => Drawable::new('d', COLOR_DEER),
+                Species::Fish => Drawable::new('f', COLOR_FISH),
+                Species::Wolf => Drawable::new('w', COLOR_WOLF),
+            };
+        }
+
+        /// Where herds are found when the world is made.
+        pub fn home_terrain(&self) -> Terrain {
+            return match self {
+                Species::Deer => Terrain::Forest,
+                Species::Fish => Terrain::Water,
+                Species::Wolf => Terrain::Hill,
+            };
+        }
+
+        /// Where members of a herd may wander.
+        pub fn roams(&self, terrain: Terrain) -> bool {
+            return match self {
+                Species::Deer => terrain == Terrain::Forest || terrain == Terrain::Meadow,
+                Species::Fish => terrain == Terrain::Water,
+                Species::Wolf => terrain != Terrain::Water && terrain != Terrain::Mountain,
+            };
+        }
+
+        pub fn herds(&self) -> usize {
+            return match self {
+                Species::Deer => 120,
+                Species::Fish => 120,
+                Species::Wolf => 40,
+            };
+        }
+
+        pub fn herd_size(&self) -> (usize, usize) {
+            return match self {
+                Species::Deer => (3, 7),
+                Species::Fish => (4, 9),
+                Species::Wolf => (2, 4),
+            };
+        }
+
+        pub fn is_predator(&self) -> bool {
+            return *self == Species::Wolf;
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Animal {
+        pub species: Species,
+        pub herd: usize,
+        pub time_since_last_move: u128,
+    }
+
+    /// The spot a herd's members stay close to. It drifts from day to day.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) struct Herd {
+        pub species: Species,
+        pub centre: Position,
+    }
+
+    /// Every herd in the world.
+    pub struct Wildlife {
+        rng: StdRng,
+        pub herds: Vec<Herd>,
+    }
+
+    fn can_stand(map: &GameMap, species: Species, x: i32, y: i32) -> bool {
+        let (width, height) = (map.width() * 3, map.height() * 3);
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return false;
+        }
+        return species.roams(map.terrain(x, y)) && !map.get_tile(x as usize, y as usize).is_built_on();
+    }
+
+    impl Wildlife {
+        pub fn new(seed: u64) -> Wildlife {
+            return Wildlife { rng: StdRng::seed_from_u64(seed), herds: Vec::new() }
+        }
+
+        /// Scatters the starting herds over their home terrain in the centre copy of the map.
+        pub fn populate(&mut self, map: &GameMap, world: &mut World) {
+            for species in Species::ALL.iter() {
+                for _ in 0..species.herds() {
+                    if let Some(centre) = self.find_home(map, *species) {
+                        let (smallest, largest) = species.herd_size();
+                        let size = self.rng.gen_range(smallest, largest + 1);
+                        self.herds.push(Herd { species: *species, centre });
+                        for _ in 0..size {
+                            self.spawn(map, world, self.herds.len() - 1);
+                        }
+                    }
+                }
+            }
+        }
+
+        fn find_home(&mut self, map: &GameMap, species: Species) -> Option<Position> {
+            return (0..100).find_map(|_| {
+                let x = self.rng.gen_range(map.width(), map.width() * 2);
+                let y = self.rng.gen_range(map.height(), map.height() * 2);
+                if map.terrain(x, y) == species.home_terrain() { Some(Position::new(x, y)) } else { None }
+            });
+        }
+
+        fn spawn(&mut self, map: &GameMap, world: &mut World, herd: usize) {
+            let Herd { species, centre } = self.herds[herd];
+            let x = centre.x + self.rng.gen_range(-HERD_RADIUS, HERD_RADIUS + 1);
+            let y = centre.y + self.rng.gen_range(-HERD_RADIUS, HERD_RADIUS + 1);
+            if !can_stand(map, species, x, y) {
+                return;
+            }
+            let animal = Animal { species, herd, time_since_last_move: 0 };
+            if species.is_predator() {
+                let wolf = world.push((Position::new(x, y), species.drawable(), animal, Danger));
+                arm(world, wolf, WOLF_HEALTH, WOLF_ATTACK, 0);
+            } else {
+                world.push((Position::new(x, y), species.drawable(), animal));
+            }
+        }
+
+        /// Herds drift, breed up to their largest size, and wolves hunt deer or starve. Herds
+        /// that die out are eventually replaced by newcomers somewhere else. Now and then a
+        /// pack of wolves sets off towards a nearby villager.
+        pub fn daily_update(&mut self, map: &GameMap, world: &mut World) {
+            let mut query = <(Entity, &Animal, &Position)>::query();
+            let animals: Vec<(Entity, Animal, Position)> = query.iter(world)
+                .map(|(entity, animal, position)| (*entity, *animal, *position))
+                .collect();
+            let mut herd_sizes: HashMap<usize, usize> = HashMap::new();
+            for (_, animal, _) in animals.iter() {
+                *herd_sizes.entry(animal.herd).or_insert(0) += 1;
+            }
+            let mut person_query = <(&Person, &Position)>::query();
+            let villagers: Vec<Position> = person_query.iter(world).map(|(_, position)| *position).collect();
+
+            let mut eaten = Vec::new();
+            for herd in 0..self.herds.len() {
+                let size = herd_sizes.get(&herd).copied().unwrap_or(0);
+                if size == 0 {
+                    self.migrate(map, world, herd);
+                    continue;
+                }
+                self.drift(map, herd);
+                let Herd { species, centre } = self.herds[herd];
+
+                let mut fed = true;
+                if species.is_predator() {
+                    let prey = animals.iter()
+                        .find(|(entity, animal, position)| {
+                            animal.species == Species::Deer && position.distance(centre) <= WOLF_HUNT_RADIUS && !eaten.contains(entity)
+                        })
+                        .map(|(entity, _, _)| *entity);
+                    fed = prey.is_some();
+                    eaten.extend(prey);
+                    if !fed && self.rng.gen_range(0, 100) < WOLF_STARVE_CHANCE {
+                        let starving = animals.iter().find(|(_, animal, _)| animal.herd == herd).map(|(entity, _, _)| *entity);
+                        eaten.extend(starving);
+                    }
+                    let prowl_to = villagers.iter().find(|villager| villager.distance(centre) <= WOLF_PROWL_RADIUS);
+                    if let Some(villager) = prowl_to {
+                        if self.rng.gen_range(0, 100) < WOLF_PROWL_CHANCE {
+                            self.herds[herd].centre = *villager;
+                        }
+                    }
+                }
+
+                if fed && size < species.herd_size().1 && self.rng.gen_range(0, 100) < BREED_CHANCE {
+                    self.spawn(map, world, herd);
+                }
+            }
+            for entity in eaten {
+                world.remove(entity);
+            }
+        }
+
+        fn migrate(&mut self, map: &GameMap, world: &mut World, herd: usize) {
+            if self.rng.gen_range(0, 100) >= MIGRATION_CHANCE {
+                return;
+            }
+            let species = self.herds[herd].species;
+            if let Some(centre) = self.find_home(map, species) {
+                self.herds[herd].centre = centre;
+                for _ in 0..species.herd_size().0 {
+                    self.spawn(map, world, herd);
+                }
+            }
+        }
+
+        fn drift(&mut self, map: &GameMap, herd: usize) {
+            let Herd { species, centre } = self.herds[herd];
+            let x = centre.x + self.rng.gen_range(-1, 2);
+            let y = centre.y + self.rng.gen_range(-1, 2);
+            if can_stand(map, species, x, y) {
+                self.herds[herd].centre = Position::new(x, y);
+            }
+        }
+    }
+
+    /// Moves animals around their herd's centre, heading back towards it when they stray.
+    pub(crate) fn animal_system(map: &GameMap, wildlife: &mut Wildlife, world: &mut World, time_delta: u128) {
+        let mut query = <(&mut Animal, &mut Position)>::query();
+        for (animal, position) in query.iter_mut(world) {
+            animal.time_since_last_move += time_delta;
+            if animal.time_since_last_move < ANIMAL_MOVE_TIME {
+                continue;
+            }
+            animal.time_since_last_move = 0;
+            let centre = wildlife.herds[animal.herd].centre;
+            let (x_step, y_step) = if position.distance(centre) > HERD_RADIUS {
+                ((centre.x - position.x).signum(), (centre.y - position.y).signum())
+            } else {
+                (wildlife.rng.gen_range(-1, 2), wildlife.rng.gen_range(-1, 2))
+            };
+            let (x, y) = (position.x + x_step, position.y + y_step);
+            if can_stand(map, animal.species, x, y) {
+                position.x = x;
+                position.y = y;
+            }
+        }
+    }
+}
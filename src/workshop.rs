@@ -0,0 +1,69 @@
+pub mod workshop {
+    use crate::settings::settings::*;
+    use crate::recipes::recipes::Recipe;
+    use crate::resources::resources::Inventory;
+    use legion::Entity;
+
+    /// A production building of the `WorkshopKind` at `kind` in the recipe book. Its workers
+    /// fetch inputs from a stockpile into its input buffer, turn them into outputs over time,
+    /// and carry the outputs from the output buffer back to a stockpile.
+    #[derive(Clone, Debug, PartialEq)]
+    pub(crate) struct Workshop {
+        pub kind: usize,
+        pub recipe: usize,
+        pub progress: u128,
+        pub workers: Vec<Entity>,
+        pub input: Inventory,
+        pub output: Inventory,
+    }
+
+    impl Workshop {
+        pub fn new(kind: usize) -> Workshop {
+            return Workshop {
+                kind,
+                recipe: 0,
+                progress: 0,
+                workers: Vec::new(),
+                input: Inventory::new(),
+                output: Inventory::new(),
+            }
+        }
+
+        /// Whether a worker turning up now would have anything to do.
+        pub fn has_work(&self, recipe: &Recipe) -> bool {
+            let can_craft = self.input.has_all(&recipe.inputs) && self.output.total() < WORKSHOP_BUFFER;
+            return can_craft || self.output.total() > 0;
+        }
+
+        /// Puts a worker's time towards the current recipe, finishing a run once enough
+        /// work has gone in.
+        pub fn work(&mut self, recipe: &Recipe, time: u128) {
+            if !self.input.has_all(&recipe.inputs) || self.output.total() >= WORKSHOP_BUFFER {
+                return;
+            }
+            self.progress += time;
+            if self.progress < recipe.time {
+                return;
+            }
+            self.progress = 0;
+            for (id, amount) in recipe.inputs.iter() {
+                self.input.take(*id, *amount);
+            }
+            for (id, amount) in recipe.outputs.iter() {
+                self.output.add(*id, *amount);
+            }
+        }
+
+        /// Switches to another recipe. Inputs for the old one are moved to the output buffer
+        /// so the workers carry them back to a stockpile.
+        pub fn set_recipe(&mut self, recipe: usize) {
+            let leftovers: Vec<_> = self.input.iter().collect();
+            for (id, amount) in leftovers {
+                self.input.take(id, amount);
+                self.output.add(id, amount);
+            }
+            self.recipe = recipe;
+            self.progress = 0;
+        }
+    }
+}